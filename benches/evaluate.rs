@@ -0,0 +1,65 @@
+//! Times schedule evaluation on a generated instance of realistic size:
+//! compiling the problem once, evaluating against the compiled problem as the
+//! solver does, `evaluate_schedule`, which compiles on every call, and, as
+//! the baseline, the evaluator from before the compiled view, which scans
+//! the problem data on every lookup.
+//!
+//! Run with `cargo bench --bench evaluate`; pass a course count to change
+//! the instance size, e.g. `cargo bench --bench evaluate -- 1000`.
+//! `tests/evaluate_snapshot.rs` checks that the compiled view reports what
+//! the baseline reported on this instance.
+
+mod support;
+#[path = "support/scan_evaluator.rs"]
+mod scan_evaluator;
+
+use scheduling_optimizer::{application::{evaluate_schedule, CompiledProblem}, domain::*};
+use std::{hint::black_box, time::{Duration, Instant}};
+use support::{build_problem, random_schedules};
+
+fn main() {
+    let courses = std::env::args().skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(400);
+    let (problem_data, constraints) = build_problem(courses);
+    let schedules = random_schedules(&problem_data, 64);
+
+    println!("{} courses, {} constraints", courses, constraints.len());
+    let compile = time_per_call(&schedules[..1], |_| {
+        black_box(CompiledProblem::new(&problem_data, &constraints));
+        0
+    });
+    println!("compile:           {:>10.1} µs", compile.as_secs_f64() * 1e6);
+
+    let problem = CompiledProblem::new(&problem_data, &constraints);
+    let compiled = time_per_call(&schedules, |schedule| problem.evaluate(schedule));
+    println!("compiled evaluate: {:>10.1} µs per schedule", compiled.as_secs_f64() * 1e6);
+
+    let per_call = time_per_call(&schedules, |schedule| evaluate_schedule(schedule, &problem_data, &constraints));
+    println!("evaluate_schedule: {:>10.1} µs per schedule", per_call.as_secs_f64() * 1e6);
+
+    let scanning = time_per_call(&schedules, |schedule| scan_evaluator::evaluate_schedule(schedule, &problem_data, &constraints));
+    println!("scanning baseline: {:>10.1} µs per schedule", scanning.as_secs_f64() * 1e6);
+
+    println!(
+        "speedup over the baseline: {:.1}x compiled, {:.1}x compiling per call",
+        scanning.as_secs_f64() / compiled.as_secs_f64(),
+        scanning.as_secs_f64() / per_call.as_secs_f64(),
+    );
+}
+
+/// Average time `evaluate` takes per schedule over about a second.
+fn time_per_call(schedules: &[Schedule], mut evaluate: impl FnMut(&Schedule) -> u32) -> Duration {
+    for schedule in schedules {
+        black_box(evaluate(schedule));
+    }
+    let started = Instant::now();
+    let mut calls = 0u32;
+    while started.elapsed() < Duration::from_secs(1) {
+        for schedule in schedules {
+            black_box(evaluate(black_box(schedule)));
+        }
+        calls += schedules.len() as u32;
+    }
+    started.elapsed() / calls
+}
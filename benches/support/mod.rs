@@ -0,0 +1,222 @@
+//! The generated instance shared by the evaluation bench and the evaluator
+//! snapshot test.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use scheduling_optimizer::domain::*;
+use serde_json::json;
+use std::collections::HashMap;
+
+const DAYS: [&str; 5] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday"];
+const STARTS: [&str; 8] = ["08:00", "09:00", "10:00", "11:00", "13:00", "14:00", "15:00", "16:00"];
+
+fn item(name: &str, set_type: SetType, fields: &[(&str, FieldType)], members: Vec<Member>) -> Item {
+    let definitions = fields.iter()
+        .map(|(field, field_type)| (field.to_string(), FieldSchema {
+            field_name: field.to_string(),
+            field_type: field_type.clone(),
+            is_required: true,
+        }))
+        .collect();
+    Item { name: name.to_string(), item_set_type: set_type, members, schema: Schema { definitions }, last_member_id: 0 }
+}
+
+fn member(id: u32, fields: Vec<(&str, Value)>) -> Member {
+    Member {
+        id: ItemId(id),
+        fields: fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect(),
+        availability: Vec::new(),
+    }
+}
+
+/// A problem with `courses` courses, a tenth as many rooms, a fifth as many
+/// lecturers, forty weekly slots and one constraint of most rule kinds.
+pub fn build_problem(courses: u32) -> (ProblemData, Vec<Constraint>) {
+    let rooms = (courses / 10).max(4);
+    let lecturers = (courses / 5).max(4);
+    let text = |s: String| Value::String(s);
+
+    let course_members = (1..=courses)
+        .map(|id| member(id, vec![
+            ("name", text(format!("Course {}", id))),
+            ("module", text(format!("Module {}", id / 4))),
+            ("kind", text(if id % 2 == 0 { "lecture" } else { "exercise" }.to_string())),
+            ("students", Value::Number(10 + (id % 9) as i32 * 10)),
+            ("duration", Value::Number(60)),
+        ]))
+        .collect();
+    let room_members = (1..=rooms)
+        .map(|id| member(id, vec![
+            ("name", text(format!("Room {}", id))),
+            ("capacity", Value::Number(20 + (id % 5) as i32 * 20)),
+            ("building", text(format!("Building {}", id % 3))),
+        ]))
+        .collect();
+    let lecturer_members = (1..=lecturers)
+        .map(|id| {
+            let mut lecturer = member(id, vec![("name", text(format!("Lecturer {}", id)))]);
+            if id % 7 == 0 {
+                lecturer.availability.push(AvailabilityWindow {
+                    kind: AvailabilityKind::Unavailable,
+                    weekday: Some("Friday".to_string()),
+                    date: None,
+                    start: None,
+                    end: None,
+                });
+            }
+            lecturer
+        })
+        .collect();
+    let slot_members = DAYS.iter()
+        .flat_map(|day| STARTS.iter().map(move |start| (day, start)))
+        .enumerate()
+        .map(|(index, (day, start))| {
+            let end = format!("{}:00", &start[..2].parse::<u32>().unwrap() + 1);
+            member(index as u32 + 1, vec![
+                ("day", text(day.to_string())),
+                ("start", Value::Date(start.to_string())),
+                ("end", Value::Date(format!("{:0>5}", end))),
+            ])
+        })
+        .collect();
+
+    let item_categories = HashMap::from([
+        ("Course".to_string(), item("Course", SetType::B_Set, &[
+            ("name", FieldType::Text),
+            ("module", FieldType::Text),
+            ("kind", FieldType::Text),
+            ("students", FieldType::Integer),
+            ("duration", FieldType::Integer),
+        ], course_members)),
+        ("Room".to_string(), item("Room", SetType::E_Set, &[
+            ("name", FieldType::Text),
+            ("capacity", FieldType::Integer),
+            ("building", FieldType::Text),
+        ], room_members)),
+        ("Lecturer".to_string(), item("Lecturer", SetType::E_Set, &[("name", FieldType::Text)], lecturer_members)),
+        ("TimeSlot".to_string(), item("TimeSlot", SetType::E_Set, &[
+            ("day", FieldType::Text),
+            ("start", FieldType::DateTime),
+            ("end", FieldType::DateTime),
+        ], slot_members)),
+    ]);
+
+    let curricula = (0..courses / 20)
+        .map(|index| Curriculum {
+            name: format!("Track {}", index),
+            task_item: "Course".to_string(),
+            tasks: (1..=5).map(|offset| ItemId(index * 20 + offset)).collect(),
+            students: Some(20),
+        })
+        .collect();
+    let location_distances = vec![
+        LocationDistance { from: "Building 0".to_string(), to: "Building 1".to_string(), minutes: 15 },
+        LocationDistance { from: "Building 1".to_string(), to: "Building 2".to_string(), minutes: 10 },
+    ];
+
+    let problem_data = ProblemData {
+        item_categories,
+        curricula,
+        pairings: Vec::new(),
+        location_distances,
+        time_dimension: None,
+        space_dimension: None,
+    };
+
+    let condition = |item: &str, field: &str, operator: &str, targets: &[&str]| json!({
+        "item_name": item, "field_key": field, "operator": operator, "target_values": targets,
+    });
+    let rules = vec![
+        json!({ "GlobalAllDifferent": { "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id" } }),
+        json!({ "GlobalAllDifferent": { "unique_item_field": "Lecturer:id", "group_item_field": "TimeSlot:id" } }),
+        json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("TimeSlot", "start", "In", &["12:00", "12:30", "13:00"])],
+            "logical_op": "Or", "mode": "Forbid",
+        } }),
+        json!({ "MultiAssignmentCheck": {
+            "conditions": [{
+                "item_name": "Room", "field_key": "capacity", "operator": "LessThan",
+                "target_field": { "item_name": "Course", "field_key": "students" },
+            }],
+            "logical_op": "And", "mode": "Forbid",
+        } }),
+        json!({ "MultiAssignmentCheck": {
+            "conditions": [
+                condition("TimeSlot", "day", "Equal", &["Friday"]),
+                condition("TimeSlot", "start", "GreaterThanOrEqual", &["14:00"]),
+            ],
+            "logical_op": "And", "mode": "Forbid",
+        } }),
+        json!({ "GlobalCardinality": {
+            "target_item_field": "Lecturer:id", "max_count": 240, "scope_conditions": null,
+            "group_item_fields": ["TimeSlot:day"], "sum_item_field": "Course:duration",
+        } }),
+        json!({ "GlobalSpread": {
+            "group_item_field": "Course:module", "spread_item_field": "TimeSlot:day", "scope_conditions": null,
+        } }),
+        json!({ "GlobalConsistency": {
+            "group_item_field": "Lecturer:id", "resource_item_field": "Room:id", "max_distinct": 3,
+            "scope_conditions": null,
+        } }),
+        json!({ "Implication": {
+            "antecedent": [condition("Course", "kind", "Equal", &["lecture"])],
+            "consequent": [condition("Room", "capacity", "GreaterThanOrEqual", &["40"])],
+        } }),
+        json!({ "GlobalTemporalPrecedence": {
+            "grouping_item_field": "Course:module",
+            "first_conditions": [condition("Course", "kind", "Equal", &["lecture"])],
+            "second_conditions": [condition("Course", "kind", "Equal", &["exercise"])],
+            "temporal_relation": "Before", "temporal_fields": ["start", "end"],
+        } }),
+        json!({ "PairwiseRelation": {
+            "first_conditions": [condition("Course", "id", "In", &["1", "2", "3", "4"])],
+            "second_conditions": [condition("Course", "id", "In", &["5", "6", "7", "8"])],
+            "relation": "DifferentDay",
+        } }),
+        json!({ "TravelTime": {
+            "resource_item_field": "Lecturer:id", "location_item_field": "Room:building", "scope_conditions": null,
+        } }),
+        json!({ "GlobalBalance": {
+            "target_item_field": "Lecturer:id", "sum_item_field": "Course:duration", "measure": "Variance",
+            "scope_conditions": null,
+        } }),
+        json!({ "CurriculumConflict": {} }),
+        json!({ "ResourceAvailability": { "level": "Hard" } }),
+        json!({ "PreferenceMatrix": {
+            "row_item": "Lecturer", "column_item": "TimeSlot", "default_score": 0,
+            "scores": [{ "row_id": 1, "column_id": 1, "score": 3 }, { "row_id": 2, "column_id": 8, "score": 2 }],
+        } }),
+        json!({ "Compactness": { "resource_item_field": "Lecturer:id", "scope_conditions": null, "measure": "IdleGaps" } }),
+        json!({ "Compactness": {
+            "resource_item_field": "Lecturer:id", "scope_conditions": null,
+            "measure": { "ConsecutiveRun": { "max_consecutive": 3 } },
+        } }),
+    ];
+    let constraints = rules.into_iter()
+        .enumerate()
+        .map(|(index, rule)| serde_json::from_value(json!({
+            "id": index + 1, "name": format!("Rule {}", index + 1), "weight": 10, "rule": rule,
+        })).expect("benchmark constraint"))
+        .collect();
+
+    (problem_data, constraints)
+}
+
+/// `count` schedules giving every course a random room, lecturer and slot;
+/// the same on every run.
+pub fn random_schedules(problem_data: &ProblemData, count: usize) -> Vec<Schedule> {
+    let mut rng = StdRng::seed_from_u64(7);
+    let size = |name: &str| problem_data.item_categories[name].members.len() as u32;
+    let courses = size("Course");
+
+    (0..count)
+        .map(|_| Schedule::new((1..=courses)
+            .map(|id| Assignment {
+                task_id: ItemId(id),
+                task_item_name: "Course".to_string(),
+                resources: ["Room", "Lecturer", "TimeSlot"].iter()
+                    .map(|name| (name.to_string(), ItemId(rng.gen_range(1..=size(name)))))
+                    .collect(),
+            })
+            .collect()))
+        .collect()
+}
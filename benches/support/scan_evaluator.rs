@@ -0,0 +1,1052 @@
+//! The evaluator as it was before the compiled problem view, less the
+//! reports: every lookup scans the problem data and rules are read afresh
+//! on every call. It is the bench's baseline only; later fixes to the
+//! evaluator are not carried over, so its costs may differ.
+
+use scheduling_optimizer::domain::{time, *};
+use std::collections::{HashMap, HashSet};
+
+pub fn evaluate_schedule(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    constraints: &[Constraint],
+) -> u32 {
+    let mut total_cost = 0;
+    
+    for constraint in constraints.iter().filter(|c| c.enabled) {
+        let violations = evaluate_constraint(&constraint.rule, schedule, problem_data);
+        total_cost += constraint_penalty(constraint, &violations);
+    }
+    
+    total_cost
+}
+
+fn constraint_penalty(constraint: &Constraint, violations: &[u32]) -> u32 {
+    let shaped: u32 = violations.iter().map(|&magnitude| constraint.penalty.apply(magnitude)).sum();
+    shaped.saturating_mul(constraint.weight)
+}
+
+/// Magnitude of each violation of the rule; how many there are and how
+/// large each is depends on the rule.
+fn evaluate_constraint(
+    rule: &ConstraintRule,
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+) -> Vec<u32> {
+    match rule {
+        ConstraintRule::MultiAssignmentCheck { conditions, logical_op, mode } => {
+            evaluate_multi_assignment(schedule, problem_data, conditions, logical_op, mode)
+        }
+        ConstraintRule::GlobalAllDifferent { unique_item_field, group_item_field } => {
+            evaluate_all_different(schedule, problem_data, unique_item_field, group_item_field)
+        }
+        ConstraintRule::GlobalCardinality {
+            target_item_field,
+            min_count,
+            max_count,
+            scope_conditions,
+            group_item_fields,
+            sum_item_field,
+            include_unused,
+        } => {
+            evaluate_cardinality(
+                schedule,
+                problem_data,
+                target_item_field,
+                *min_count,
+                *max_count,
+                scope_conditions,
+                group_item_fields,
+                sum_item_field.as_deref(),
+                *include_unused,
+            )
+        }
+        ConstraintRule::GlobalTemporalPrecedence { 
+            grouping_item_field, 
+            first_conditions, 
+            second_conditions, 
+            temporal_relation, 
+            temporal_fields,
+            temporal_item,
+        } => {
+            evaluate_temporal_precedence(
+                schedule, 
+                problem_data, 
+                grouping_item_field, 
+                first_conditions, 
+                second_conditions, 
+                temporal_relation, 
+                (temporal_item.as_deref().unwrap_or(&problem_data.time().item_name), temporal_fields),
+            )
+        }
+        ConstraintRule::Implication { antecedent, consequent } => {
+            evaluate_implication(schedule, problem_data, antecedent, consequent)
+        }
+        ConstraintRule::PairwiseRelation { first_conditions, second_conditions, relation } => {
+            evaluate_pairwise(schedule, problem_data, first_conditions, second_conditions, relation)
+        }
+        ConstraintRule::GlobalSpread { group_item_field, spread_item_field, min_distinct, scope_conditions } => {
+            evaluate_spread(schedule, problem_data, group_item_field, spread_item_field, *min_distinct, scope_conditions)
+        }
+        ConstraintRule::GlobalConsistency { group_item_field, resource_item_field, max_distinct, scope_conditions } => {
+            evaluate_consistency(schedule, problem_data, group_item_field, resource_item_field, *max_distinct, scope_conditions)
+        }
+        ConstraintRule::TravelTime { resource_item_field, location_item_field, scope_conditions } => {
+            evaluate_travel_time(schedule, problem_data, resource_item_field, location_item_field, scope_conditions)
+        }
+        ConstraintRule::GlobalBalance { target_item_field, sum_item_field, measure, scope_conditions } => {
+            evaluate_balance(schedule, problem_data, target_item_field, sum_item_field.as_deref(), measure, scope_conditions)
+        }
+        ConstraintRule::CurriculumConflict { curricula } => {
+            evaluate_curriculum_conflict(schedule, problem_data, curricula)
+        }
+        ConstraintRule::ResourceAvailability { level, item_names } => {
+            evaluate_availability(schedule, problem_data, *level, item_names)
+        }
+        ConstraintRule::PreferenceMatrix { row_item, column_item, default_score, scores } => {
+            evaluate_preferences(schedule, row_item, column_item, *default_score, scores)
+        }
+        ConstraintRule::Compactness { resource_item_field, scope_conditions, measure } => {
+            evaluate_compactness(schedule, problem_data, resource_item_field, scope_conditions, measure)
+        }
+    }
+}
+
+fn evaluate_multi_assignment(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    conditions: &ConditionSet,
+    logical_op: &LogicalOperator,
+    mode: &ConstraintMode,
+) -> Vec<u32> {
+    let mut violations = Vec::new();
+    
+    for assignment in &schedule.assignments {
+        let combined = evaluate_condition_set(conditions, logical_op, assignment, problem_data);
+        
+        match mode {
+            ConstraintMode::Forbid if combined => violations.push(1),
+            ConstraintMode::Require if !combined => violations.push(1),
+            _ => {}
+        }
+    }
+    
+    violations
+}
+
+fn evaluate_implication(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    antecedent: &ConditionSet,
+    consequent: &ConditionSet,
+) -> Vec<u32> {
+    schedule.assignments.iter()
+        .filter(|a| matches_filter(antecedent, a, problem_data) && !matches_filter(consequent, a, problem_data))
+        .map(|_| 1)
+        .collect()
+}
+
+fn evaluate_condition_set(
+    conditions: &ConditionSet,
+    logical_op: &LogicalOperator,
+    assignment: &Assignment,
+    problem_data: &ProblemData,
+) -> bool {
+    match conditions {
+        ConditionSet::List(list) => match logical_op {
+            LogicalOperator::And => list.iter().all(|c| evaluate_condition(c, assignment, problem_data)),
+            LogicalOperator::Or => list.iter().any(|c| evaluate_condition(c, assignment, problem_data)),
+        },
+        ConditionSet::Expr(expr) => evaluate_expr(expr, assignment, problem_data),
+    }
+}
+
+fn evaluate_expr(expr: &ConditionExpr, assignment: &Assignment, problem_data: &ProblemData) -> bool {
+    match expr {
+        ConditionExpr::Condition(condition) => evaluate_condition(condition, assignment, problem_data),
+        ConditionExpr::And(exprs) => exprs.iter().all(|e| evaluate_expr(e, assignment, problem_data)),
+        ConditionExpr::Or(exprs) => exprs.iter().any(|e| evaluate_expr(e, assignment, problem_data)),
+        ConditionExpr::Not(expr) => !evaluate_expr(expr, assignment, problem_data),
+        ConditionExpr::Implies { antecedent, consequent } => {
+            !evaluate_expr(antecedent, assignment, problem_data) || evaluate_expr(consequent, assignment, problem_data)
+        }
+    }
+}
+
+/// Whether the assignment satisfies a rule's filter conditions, which are
+/// always combined with AND when given as a list.
+fn matches_filter(conditions: &ConditionSet, assignment: &Assignment, problem_data: &ProblemData) -> bool {
+    evaluate_condition_set(conditions, &LogicalOperator::And, assignment, problem_data)
+}
+
+fn evaluate_condition(
+    condition: &Condition,
+    assignment: &Assignment,
+    problem_data: &ProblemData,
+) -> bool {
+    let value = match field_value(assignment, problem_data, &condition.item_name, &condition.field_key) {
+        Some(v) => v,
+        None => return false,
+    };
+
+    match &condition.target_field {
+        Some(operand) => {
+            let other = match field_value(assignment, problem_data, &operand.item_name, &operand.field_key) {
+                Some(v) => v,
+                None => return false,
+            };
+            compare_with_operand(&value, &condition.operator, &other, operand)
+        }
+        None => compare_value(&value, &condition.operator, &condition.target_values),
+    }
+}
+
+/// Compares `value` against another field of the same assignment, after
+/// applying the operand's multiplier and offset. Offsets on DateTime fields
+/// are minutes.
+fn compare_with_operand(
+    value: &Value,
+    operator: &ComparisonOperator,
+    other: &Value,
+    operand: &FieldOperand,
+) -> bool {
+    match (value, other) {
+        (Value::Number(n), Value::Number(m)) => {
+            let target = *m as f64 * operand.multiplier.unwrap_or(1.0) + operand.offset.unwrap_or(0) as f64;
+            compare_ordered(&(*n as f64), operator, &target)
+        }
+        (Value::Date(a), Value::Date(b)) if operand.offset.is_some() => {
+            match (time::parse_clock(a), time::parse_clock(b)) {
+                (Some(a), Some(b)) => compare_ordered(&(a as i64), operator, &(b as i64 + operand.offset.unwrap_or(0) as i64)),
+                _ => false,
+            }
+        }
+        (Value::String(_), Value::String(t)) | (Value::Date(_), Value::Date(t)) => {
+            compare_value(value, operator, std::slice::from_ref(t))
+        }
+        _ => false,
+    }
+}
+
+fn compare_ordered<T: PartialOrd>(value: &T, operator: &ComparisonOperator, target: &T) -> bool {
+    match operator {
+        ComparisonOperator::Equal | ComparisonOperator::In => value == target,
+        ComparisonOperator::NotEqual | ComparisonOperator::NotIn => value != target,
+        ComparisonOperator::GreaterThan | ComparisonOperator::After => value > target,
+        ComparisonOperator::GreaterThanOrEqual => value >= target,
+        ComparisonOperator::LessThan | ComparisonOperator::Before => value < target,
+        ComparisonOperator::LessThanOrEqual => value <= target,
+        ComparisonOperator::Overlap | ComparisonOperator::NoOverlap => false,
+    }
+}
+
+fn compare_value(value: &Value, operator: &ComparisonOperator, targets: &[String]) -> bool {
+    match (value, operator) {
+        // ---------- Numbers ----------
+        (Value::Number(n), op) => {
+            // parse all targets as i32 (ignore ones that fail to parse)
+            let parsed: Vec<i32> = targets
+                .iter()
+                .filter_map(|t| t.parse::<i32>().ok())
+                .collect();
+
+            if parsed.is_empty() {
+                return false;
+            }
+
+            match op {
+                ComparisonOperator::Equal => parsed.iter().any(|t| n == t),
+                ComparisonOperator::NotEqual => parsed.iter().all(|t| n != t),
+                ComparisonOperator::In => parsed.iter().any(|t| n == t),
+                ComparisonOperator::NotIn => parsed.iter().all(|t| n != t),
+
+                ComparisonOperator::GreaterThan => parsed.iter().any(|t| n > t),
+                ComparisonOperator::GreaterThanOrEqual => parsed.iter().any(|t| n >= t),
+                ComparisonOperator::LessThan => parsed.iter().any(|t| n < t),
+                ComparisonOperator::LessThanOrEqual => parsed.iter().any(|t| n <= t),
+
+                // Before / After / Overlap / NoOverlap don't have a useful numeric meaning here
+                _ => false,
+            }
+        }
+
+        // ---------- Strings ----------
+        (Value::String(s), op) => {
+            if targets.is_empty() {
+                return false;
+            }
+
+            match op {
+                ComparisonOperator::Equal => targets.iter().any(|t| s == t),
+                ComparisonOperator::NotEqual => targets.iter().all(|t| s != t),
+                ComparisonOperator::In => targets.iter().any(|t| s == t),
+                ComparisonOperator::NotIn => targets.iter().all(|t| s != t),
+
+                // Lexicographic comparisons
+                ComparisonOperator::GreaterThan => targets.iter().any(|t| s > t),
+                ComparisonOperator::GreaterThanOrEqual => targets.iter().any(|t| s >= t),
+                ComparisonOperator::LessThan => targets.iter().any(|t| s < t),
+                ComparisonOperator::LessThanOrEqual => targets.iter().any(|t| s <= t),
+
+                // Temporal-ish operators on plain strings: treat as lexicographic dates/times if caller uses them
+                ComparisonOperator::Before => targets.iter().any(|t| s < t),
+                ComparisonOperator::After => targets.iter().any(|t| s > t),
+
+                // Overlap / NoOverlap on strings: treat targets as a range [min, max]
+                ComparisonOperator::Overlap => {
+                    let min = targets.iter().min().unwrap();
+                    let max = targets.iter().max().unwrap();
+                    s >= min && s <= max
+                }
+                ComparisonOperator::NoOverlap => {
+                    let min = targets.iter().min().unwrap();
+                    let max = targets.iter().max().unwrap();
+                    s < min || s > max
+                }
+            }
+        }
+
+        // ---------- Dates (stored as String, e.g. "13:30" or ISO) ----------
+        (Value::Date(d), op) => {
+            if targets.is_empty() {
+                return false;
+            }
+
+            match op {
+                ComparisonOperator::Equal => targets.iter().any(|t| d == t),
+                ComparisonOperator::NotEqual => targets.iter().all(|t| d != t),
+                ComparisonOperator::In => targets.iter().any(|t| d == t),
+                ComparisonOperator::NotIn => targets.iter().all(|t| d != t),
+
+                ComparisonOperator::GreaterThan => targets.iter().any(|t| d > t),
+                ComparisonOperator::GreaterThanOrEqual => targets.iter().any(|t| d >= t),
+                ComparisonOperator::LessThan => targets.iter().any(|t| d < t),
+                ComparisonOperator::LessThanOrEqual => targets.iter().any(|t| d <= t),
+
+                // For scalar dates, Before / After are just < and >
+                ComparisonOperator::Before => targets.iter().any(|t| d < t),
+                ComparisonOperator::After => targets.iter().any(|t| d > t),
+
+                // Overlap / NoOverlap: treat targets as an interval [min, max]
+                ComparisonOperator::Overlap => {
+                    let min = targets.iter().min().unwrap();
+                    let max = targets.iter().max().unwrap();
+                    d >= min && d <= max
+                }
+                ComparisonOperator::NoOverlap => {
+                    let min = targets.iter().min().unwrap();
+                    let max = targets.iter().max().unwrap();
+                    d < min || d > max
+                }
+            }
+        }
+    }
+}
+
+
+fn evaluate_all_different(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    unique_item_field: &str,
+    group_item_field: &str,
+) -> Vec<u32> {
+    let Some(groups) = group_values(schedule, problem_data, group_item_field, unique_item_field, &None) else {
+        return Vec::new();
+    };
+    
+    let mut violations = Vec::new();
+    for values in groups.values() {
+        let unique_count = values.iter().collect::<HashSet<_>>().len();
+        if unique_count < values.len() {
+            violations.push((values.len() - unique_count) as u32);
+        }
+    }
+    
+    violations
+}
+
+fn evaluate_spread(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    group_item_field: &str,
+    spread_item_field: &str,
+    min_distinct: Option<u32>,
+    scope_conditions: &Option<ConditionSet>,
+) -> Vec<u32> {
+    let Some(groups) = group_values(schedule, problem_data, group_item_field, spread_item_field, scope_conditions) else {
+        return Vec::new();
+    };
+
+    let mut violations = Vec::new();
+    for values in groups.values() {
+        let distinct = values.iter().collect::<HashSet<_>>().len() as u32;
+        let wanted = match min_distinct {
+            Some(min_distinct) => min_distinct.min(values.len() as u32),
+            None => values.len() as u32,
+        };
+        violations.push(wanted.saturating_sub(distinct));
+    }
+
+    violations
+}
+
+fn evaluate_consistency(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    group_item_field: &str,
+    resource_item_field: &str,
+    max_distinct: u32,
+    scope_conditions: &Option<ConditionSet>,
+) -> Vec<u32> {
+    let Some(groups) = group_values(schedule, problem_data, group_item_field, resource_item_field, scope_conditions) else {
+        return Vec::new();
+    };
+
+    groups.values()
+        .map(|values| (values.iter().collect::<HashSet<_>>().len() as u32).saturating_sub(max_distinct))
+        .collect()
+}
+
+/// Values of `value_item_field` for the assignments in scope, grouped by
+/// their value of `group_item_field`.
+fn group_values(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    group_item_field: &str,
+    value_item_field: &str,
+    scope_conditions: &Option<ConditionSet>,
+) -> Option<HashMap<String, Vec<String>>> {
+    let (group_item, group_field) = parse_item_field(group_item_field)?;
+    let (value_item, value_field) = parse_item_field(value_item_field)?;
+    
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    
+    for assignment in &schedule.assignments {
+        if scope_conditions.as_ref().is_some_and(|c| !matches_filter(c, assignment, problem_data)) {
+            continue;
+        }
+        let group_value = extract_field_value(assignment, problem_data, group_item, group_field);
+        let value = extract_field_value(assignment, problem_data, value_item, value_field);
+        
+        groups.entry(group_value).or_default().push(value);
+    }
+
+    Some(groups)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn evaluate_cardinality(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    target_item_field: &str,
+    min_count: Option<u32>,
+    max_count: Option<u32>,
+    scope_conditions: &Option<ConditionSet>,
+    group_item_fields: &[String],
+    sum_item_field: Option<&str>,
+    include_unused: bool,
+) -> Vec<u32> {
+    let Some((target_item, target_field)) = parse_item_field(target_item_field) else {
+        return Vec::new();
+    };
+    let Some(groups) = group_item_fields.iter().map(|f| parse_item_field(f)).collect::<Option<Vec<_>>>() else {
+        return Vec::new();
+    };
+    let sum = match sum_item_field.map(parse_item_field) {
+        Some(None) => return Vec::new(),
+        Some(Some(sum)) => Some(sum),
+        None => None,
+    };
+    
+    let filtered: Vec<&Assignment> = if let Some(conditions) = scope_conditions {
+        schedule.assignments.iter()
+            .filter(|a| matches_filter(conditions, a, problem_data))
+            .collect()
+    } else {
+        schedule.assignments.iter().collect()
+    };
+    
+    let mut counts: HashMap<(String, Vec<String>), u32> = HashMap::new();
+    for assignment in filtered {
+        let value = extract_field_value(assignment, problem_data, target_item, target_field);
+        let group_values: Vec<String> = groups.iter()
+            .map(|(item, field)| extract_field_value(assignment, problem_data, item, field))
+            .collect();
+        *counts.entry((value, group_values)).or_default() += assignment_amount(assignment, problem_data, sum);
+    }
+
+    if include_unused {
+        let mut group_keys: HashSet<Vec<String>> = counts.keys().map(|(_, g)| g.clone()).collect();
+        if group_keys.is_empty() && groups.is_empty() {
+            group_keys.insert(Vec::new());
+        }
+        if let Some(item) = problem_data.item_categories.get(target_item) {
+            for member in &item.members {
+                let value = member_field_value(member, target_field).map(value_to_string).unwrap_or_default();
+                for group_key in &group_keys {
+                    counts.entry((value.clone(), group_key.clone())).or_default();
+                }
+            }
+        }
+    }
+    
+    let mut violations = Vec::new();
+    for count in counts.values() {
+        if let Some(max_count) = max_count {
+            violations.push(count.saturating_sub(max_count));
+        }
+        if let Some(min_count) = min_count {
+            violations.push(min_count.saturating_sub(*count));
+        }
+    }
+    
+    violations
+}
+
+fn evaluate_balance(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    target_item_field: &str,
+    sum_item_field: Option<&str>,
+    measure: &BalanceMeasure,
+    scope_conditions: &Option<ConditionSet>,
+) -> Vec<u32> {
+    let Some((target_item, target_field)) = parse_item_field(target_item_field) else {
+        return Vec::new();
+    };
+    let sum = match sum_item_field.map(parse_item_field) {
+        Some(None) => return Vec::new(),
+        Some(Some(sum)) => Some(sum),
+        None => None,
+    };
+
+    // Every member takes part, including those with nothing assigned
+    let mut totals: HashMap<String, u32> = HashMap::new();
+    if let Some(item) = problem_data.item_categories.get(target_item) {
+        for member in &item.members {
+            let value = member_field_value(member, target_field).map(value_to_string).unwrap_or_default();
+            totals.entry(value).or_default();
+        }
+    }
+    for assignment in &schedule.assignments {
+        if scope_conditions.as_ref().is_some_and(|c| !matches_filter(c, assignment, problem_data)) {
+            continue;
+        }
+        let Some(value) = field_value(assignment, problem_data, target_item, target_field) else {
+            continue;
+        };
+        *totals.entry(value_to_string(value)).or_default() += assignment_amount(assignment, problem_data, sum);
+    }
+
+    if totals.is_empty() {
+        return Vec::new();
+    }
+    let values: Vec<f64> = totals.values().map(|&t| t as f64).collect();
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let deviation = match measure {
+        BalanceMeasure::Range => {
+            let max = values.iter().cloned().fold(f64::MIN, f64::max);
+            let min = values.iter().cloned().fold(f64::MAX, f64::min);
+            max - min
+        }
+        BalanceMeasure::Variance => values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64,
+        BalanceMeasure::AbsoluteDeviation => values.iter().map(|v| (v - mean).abs()).sum(),
+    };
+
+    vec![deviation.round() as u32]
+}
+
+/// What an assignment adds to a total: one, or the value of the Integer
+/// field `sum` when given.
+fn assignment_amount(assignment: &Assignment, problem_data: &ProblemData, sum: Option<(&str, &str)>) -> u32 {
+    match sum {
+        Some((item, field)) => match field_value(assignment, problem_data, item, field) {
+            Some(Value::Number(n)) => n.max(0) as u32,
+            _ => 0,
+        },
+        None => 1,
+    }
+}
+
+fn evaluate_temporal_precedence(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    grouping_item_field: &str,
+    first_conditions: &ConditionSet,
+    second_conditions: &ConditionSet,
+    temporal_relation: &ComparisonOperator,
+    (temporal_item, temporal_fields): (&str, &[String]),
+) -> Vec<u32> {
+    let Some((group_item, group_field)) = parse_item_field(grouping_item_field) else {
+        return Vec::new();
+    };
+    
+    let mut groups: HashMap<String, Vec<&Assignment>> = HashMap::new();
+    for assignment in &schedule.assignments {
+        let group_value = extract_field_value(assignment, problem_data, group_item, group_field);
+        groups.entry(group_value).or_default().push(assignment);
+    }
+    
+    let mut violations = Vec::new();
+    for group_assignments in groups.values() {
+        let firsts: Vec<&&Assignment> = group_assignments.iter()
+            .filter(|a| matches_filter(first_conditions, a, problem_data))
+            .collect();
+        
+        let seconds: Vec<&&Assignment> = group_assignments.iter()
+            .filter(|a| matches_filter(second_conditions, a, problem_data))
+            .collect();
+        
+        for first in &firsts {
+            for second in &seconds {
+                if !check_temporal_relation(first, second, problem_data, temporal_relation, temporal_item, temporal_fields) {
+                    violations.push(1);
+                }
+            }
+        }
+    }
+    
+    violations
+}
+
+fn check_temporal_relation(
+    first: &Assignment,
+    second: &Assignment,
+    problem_data: &ProblemData,
+    relation: &ComparisonOperator,
+    temporal_item: &str,
+    temporal_fields: &[String],
+) -> bool {
+    let (start_field, end_field) = match temporal_fields {
+        [field] => (field, field),
+        [start, end] => (start, end),
+        _ => return false,
+    };
+    let date = |assignment: &Assignment, field: &str| match field_value(assignment, problem_data, temporal_item, field) {
+        Some(Value::Date(v)) => Some(v),
+        _ => None,
+    };
+
+    let (Some(first_start), Some(first_end), Some(second_start), Some(second_end)) = (
+        date(first, start_field),
+        date(first, end_field),
+        date(second, start_field),
+        date(second, end_field),
+    ) else {
+        return false;
+    };
+
+    match relation {
+        ComparisonOperator::Before => first_end < second_start,
+        ComparisonOperator::After => first_start > second_end,
+        ComparisonOperator::Overlap => first_start < second_end && second_start < first_end,
+        ComparisonOperator::NoOverlap => !(first_start < second_end && second_start < first_end),
+
+        ComparisonOperator::Equal => first_start == second_start,
+        ComparisonOperator::NotEqual => first_start != second_start,
+        ComparisonOperator::GreaterThan => first_start > second_start,
+        ComparisonOperator::GreaterThanOrEqual => first_start >= second_start,
+        ComparisonOperator::LessThan => first_start < second_start,
+        ComparisonOperator::LessThanOrEqual => first_start <= second_start,
+
+        _ => false,
+    }
+}
+
+fn evaluate_pairwise(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    first_conditions: &ConditionSet,
+    second_conditions: &ConditionSet,
+    relation: &PairRelation,
+) -> Vec<u32> {
+    let firsts: Vec<(usize, &Assignment)> = schedule.assignments.iter().enumerate()
+        .filter(|(_, a)| matches_filter(first_conditions, a, problem_data))
+        .collect();
+    let seconds: Vec<(usize, &Assignment)> = schedule.assignments.iter().enumerate()
+        .filter(|(_, a)| matches_filter(second_conditions, a, problem_data))
+        .collect();
+
+    let mut violations = Vec::new();
+    for (i, first) in &firsts {
+        for (j, second) in &seconds {
+            if i != j && !check_pair_relation(first, second, problem_data, relation) {
+                violations.push(1);
+            }
+        }
+    }
+
+    violations
+}
+
+fn check_pair_relation(
+    first: &Assignment,
+    second: &Assignment,
+    problem_data: &ProblemData,
+    relation: &PairRelation,
+) -> bool {
+    let space = &problem_data.space().item_name;
+    let rooms = || (first.resources.get(space), second.resources.get(space));
+    let spans = || (time_span(first, problem_data), time_span(second, problem_data));
+
+    match relation {
+        PairRelation::SameRoom => matches!(rooms(), (Some(a), Some(b)) if a == b),
+        PairRelation::DifferentRoom => matches!(rooms(), (Some(a), Some(b)) if a != b),
+        PairRelation::SameTime => matches!(spans(), (Some(a), Some(b)) if a.overlaps(&b)),
+        PairRelation::DifferentTime => matches!(spans(), (Some(a), Some(b)) if !a.overlaps(&b)),
+        PairRelation::SameDay => matches!(spans(), (Some(a), Some(b)) if a.day == b.day),
+        PairRelation::DifferentDay => matches!(spans(), (Some(a), Some(b)) if a.day != b.day),
+        PairRelation::MinutesApart(minutes) => matches!(
+            spans(),
+            (Some(a), Some(b)) if a.day != b.day || b.start >= a.end + minutes || a.start >= b.end + minutes
+        ),
+        PairRelation::Before => matches!(spans(), (Some(a), Some(b)) if a.ends_before(&b)),
+        PairRelation::After => matches!(spans(), (Some(a), Some(b)) if b.ends_before(&a)),
+    }
+}
+
+fn evaluate_travel_time(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    resource_item_field: &str,
+    location_item_field: &str,
+    scope_conditions: &Option<ConditionSet>,
+) -> Vec<u32> {
+    let (Some((resource_item, resource_field)), Some((location_item, location_field))) =
+        (parse_item_field(resource_item_field), parse_item_field(location_item_field))
+    else {
+        return Vec::new();
+    };
+
+    let mut days = HashMap::new();
+    for assignment in &schedule.assignments {
+        if scope_conditions.as_ref().is_some_and(|c| !matches_filter(c, assignment, problem_data)) {
+            continue;
+        }
+        let Some(resource) = field_value(assignment, problem_data, resource_item, resource_field) else {
+            continue;
+        };
+        let Some(span) = time_span(assignment, problem_data) else {
+            continue;
+        };
+        let location = extract_field_value(assignment, problem_data, location_item, location_field);
+        days.entry((value_to_string(resource), span.day.clone())).or_insert_with(Vec::new).push((span, location));
+    }
+
+    let mut violations = Vec::new();
+    for lessons in days.values_mut() {
+        lessons.sort_by_key(|(span, _)| span.start);
+        for pair in lessons.windows(2) {
+            let ((first, from), (second, to)) = (&pair[0], &pair[1]);
+            if problem_data.travel_minutes(from, to) > second.start.saturating_sub(first.end) {
+                violations.push(1);
+            }
+        }
+    }
+
+    violations
+}
+
+fn evaluate_curriculum_conflict(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    curricula: &[String],
+) -> Vec<u32> {
+    let mut violations = Vec::new();
+    for curriculum in &problem_data.curricula {
+        if !curricula.is_empty() && !curricula.contains(&curriculum.name) {
+            continue;
+        }
+        let spans: Vec<TimeSpan> = schedule.assignments.iter()
+            .filter(|a| curriculum.includes(&a.task_item_name, a.task_id))
+            .filter_map(|a| time_span(a, problem_data))
+            .collect();
+        for (i, first) in spans.iter().enumerate() {
+            for second in &spans[i + 1..] {
+                if first.overlaps(second) {
+                    violations.push(curriculum.students.unwrap_or(1));
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+fn evaluate_availability(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    level: AvailabilityLevel,
+    item_names: &[String],
+) -> Vec<u32> {
+    let mut violations = Vec::new();
+    for assignment in &schedule.assignments {
+        let Some(span) = time_span(assignment, problem_data) else {
+            continue;
+        };
+        for (item_name, member_id) in &assignment.resources {
+            if !item_names.is_empty() && !item_names.contains(item_name) {
+                continue;
+            }
+            let Some(member) = problem_data.item_categories.get(item_name).and_then(|i| i.member(*member_id)) else {
+                continue;
+            };
+            if availability_breach(&member.availability, &span) == Some(level) {
+                violations.push(1);
+            }
+        }
+    }
+
+    violations
+}
+
+/// The worst way `span` breaches the windows, if it does.
+fn availability_breach(windows: &[AvailabilityWindow], span: &TimeSpan) -> Option<AvailabilityLevel> {
+    let of_kind = |kind| windows.iter().filter(move |w| w.kind == kind);
+
+    let has_available = of_kind(AvailabilityKind::Available).next().is_some();
+    let inside_available = of_kind(AvailabilityKind::Available).any(|w| window_contains(w, span));
+    let unavailable = of_kind(AvailabilityKind::Unavailable).any(|w| window_overlaps(w, span));
+    if unavailable || (has_available && !inside_available) {
+        return Some(AvailabilityLevel::Hard);
+    }
+    if of_kind(AvailabilityKind::PreferNot).any(|w| window_overlaps(w, span)) {
+        return Some(AvailabilityLevel::PreferNot);
+    }
+    None
+}
+
+/// Start and end of the window in minutes, if it applies on the span's day.
+fn window_bounds(window: &AvailabilityWindow, span: &TimeSpan) -> Option<(u32, u32)> {
+    let day = span.day.as_deref();
+    if let Some(weekday) = &window.weekday {
+        if day.and_then(time::weekday_of) != Some(time::weekday_index(weekday)?) {
+            return None;
+        }
+    }
+    if let Some(date) = &window.date {
+        if day.and_then(time::parse_date) != Some(time::parse_date(date)?) {
+            return None;
+        }
+    }
+    let start = window.start.as_deref().map_or(Some(0), time::parse_clock)?;
+    let end = window.end.as_deref().map_or(Some(24 * 60), time::parse_clock)?;
+    Some((start, end))
+}
+
+fn window_contains(window: &AvailabilityWindow, span: &TimeSpan) -> bool {
+    window_bounds(window, span).is_some_and(|(start, end)| start <= span.start && span.end <= end)
+}
+
+fn window_overlaps(window: &AvailabilityWindow, span: &TimeSpan) -> bool {
+    window_bounds(window, span).is_some_and(|(start, end)| span.start < end && start < span.end.max(span.start + 1))
+}
+
+fn evaluate_preferences(
+    schedule: &Schedule,
+    row_item: &str,
+    column_item: &str,
+    default_score: u32,
+    scores: &[PreferenceScore],
+) -> Vec<u32> {
+    let lookup: HashMap<(ItemId, ItemId), u32> = scores.iter()
+        .map(|s| ((s.row_id, s.column_id), s.score))
+        .collect();
+
+    schedule.assignments.iter()
+        .filter_map(|a| Some((assignment_member(a, row_item)?, assignment_member(a, column_item)?)))
+        .map(|pair| lookup.get(&pair).copied().unwrap_or(default_score))
+        .collect()
+}
+
+fn evaluate_compactness(
+    schedule: &Schedule,
+    problem_data: &ProblemData,
+    resource_item_field: &str,
+    scope_conditions: &Option<ConditionSet>,
+    measure: &CompactnessMeasure,
+) -> Vec<u32> {
+    let Some((resource_item, resource_field)) = parse_item_field(resource_item_field) else {
+        return Vec::new();
+    };
+
+    let mut lessons: HashMap<String, HashMap<Option<String>, Vec<TimeSpan>>> = HashMap::new();
+    for assignment in &schedule.assignments {
+        if scope_conditions.as_ref().is_some_and(|c| !matches_filter(c, assignment, problem_data)) {
+            continue;
+        }
+        let Some(resource) = field_value(assignment, problem_data, resource_item, resource_field) else {
+            continue;
+        };
+        let Some(span) = time_span(assignment, problem_data) else {
+            continue;
+        };
+        lessons.entry(value_to_string(resource)).or_default()
+            .entry(span.day.clone()).or_default()
+            .push(span);
+    }
+
+    let dimension = problem_data.time();
+    let slots: Vec<TimeSpan> = match (measure, problem_data.item_categories.get(&dimension.item_name)) {
+        (CompactnessMeasure::IdleGaps, Some(item)) => {
+            item.members.iter().filter_map(|m| member_time_span(m, dimension)).collect()
+        }
+        _ => Vec::new(),
+    };
+
+    let mut violations = Vec::new();
+    for days in lessons.values_mut() {
+        match measure {
+            CompactnessMeasure::TeachingDays { max_days } => {
+                violations.push((days.len() as u32).saturating_sub(*max_days));
+            }
+            CompactnessMeasure::IsolatedLessons => {
+                violations.extend(days.values().filter(|spans| spans.len() == 1).map(|_| 1));
+            }
+            CompactnessMeasure::IdleGaps => {
+                for (day, spans) in days.iter() {
+                    let first_start = spans.iter().map(|s| s.start).min().unwrap_or_default();
+                    let last_end = spans.iter().map(|s| s.end).max().unwrap_or_default();
+                    violations.push(slots.iter()
+                        .filter(|slot| slot.day == *day && slot.start >= first_start && slot.end <= last_end)
+                        .filter(|slot| !spans.iter().any(|s| s.overlaps(slot)))
+                        .count() as u32);
+                }
+            }
+            CompactnessMeasure::ConsecutiveRun { max_consecutive, max_break_minutes } => {
+                for spans in days.values_mut() {
+                    spans.sort_by_key(|s| s.start);
+                    let mut run: u32 = 0;
+                    let mut run_end = 0;
+                    for span in spans.iter() {
+                        if run > 0 && span.start <= run_end + max_break_minutes {
+                            run += 1;
+                        } else {
+                            violations.push(run.saturating_sub(*max_consecutive));
+                            run = 1;
+                        }
+                        run_end = run_end.max(span.end);
+                    }
+                    violations.push(run.saturating_sub(*max_consecutive));
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+/// When an assignment takes place: its day (a weekday name or ISO date) and
+/// start and end in minutes since midnight.
+#[derive(Clone, Debug, PartialEq)]
+struct TimeSpan {
+    day: Option<String>,
+    start: u32,
+    end: u32,
+}
+
+impl TimeSpan {
+    fn overlaps(&self, other: &TimeSpan) -> bool {
+        self.day == other.day && self.start < other.end && other.start < self.end
+    }
+
+    fn ends_before(&self, other: &TimeSpan) -> bool {
+        if self.day == other.day {
+            return self.end <= other.start;
+        }
+        match (&self.day, &other.day) {
+            (Some(a), Some(b)) => match (time::weekday_index(a), time::weekday_index(b)) {
+                (Some(a), Some(b)) => a < b,
+                _ => a < b,
+            },
+            _ => false,
+        }
+    }
+}
+
+fn time_span(assignment: &Assignment, problem_data: &ProblemData) -> Option<TimeSpan> {
+    let dimension = problem_data.time();
+    span_from_fields(dimension, |field_key| field_value(assignment, problem_data, &dimension.item_name, field_key))
+}
+
+/// Span of a member of the time item itself, e.g. a free slot.
+fn member_time_span(member: &Member, dimension: &TimeDimension) -> Option<TimeSpan> {
+    span_from_fields(dimension, |field_key| member_field_value(member, field_key))
+}
+
+fn span_from_fields(dimension: &TimeDimension, value_of: impl Fn(&str) -> Option<Value>) -> Option<TimeSpan> {
+    let start = value_of(&dimension.start_field)?;
+    let end = value_of(&dimension.end_field).unwrap_or_else(|| start.clone());
+    let (Value::Date(start), Value::Date(end)) = (start, end) else {
+        return None;
+    };
+
+    let day = match value_of(&dimension.day_field) {
+        Some(Value::String(day)) | Some(Value::Date(day)) => Some(day),
+        _ => time::parse_date(&start).map(|(y, m, d)| format!("{:04}-{:02}-{:02}", y, m, d)),
+    };
+
+    Some(TimeSpan {
+        day,
+        start: time::parse_clock(&start)?,
+        end: time::parse_clock(&end)?,
+    })
+}
+
+fn parse_item_field(item_field: &str) -> Option<(&str, &str)> {
+    item_field.split_once(':')
+}
+
+fn extract_field_value(
+    assignment: &Assignment,
+    problem_data: &ProblemData,
+    item_name: &str,
+    field_key: &str,
+) -> String {
+    field_value(assignment, problem_data, item_name, field_key)
+        .map(value_to_string)
+        .unwrap_or_default()
+}
+
+fn value_to_string(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        Value::Number(n) => n.to_string(),
+        Value::Date(d) => d,
+    }
+}
+
+/// The member the assignment uses from `item_name`, whether it is the task
+/// itself or one of its resources.
+fn assignment_member(assignment: &Assignment, item_name: &str) -> Option<ItemId> {
+    if item_name == assignment.task_item_name {
+        Some(assignment.task_id)
+    } else {
+        assignment.resources.get(item_name).copied()
+    }
+}
+
+/// Value of `item_name:field_key` for the member the assignment uses from
+/// that item.
+fn field_value(
+    assignment: &Assignment,
+    problem_data: &ProblemData,
+    item_name: &str,
+    field_key: &str,
+) -> Option<Value> {
+    let member_id = assignment_member(assignment, item_name)?;
+    
+    if field_key == "id" {
+        return Some(Value::Number(member_id.0 as i32));
+    }
+    
+    let member = problem_data.item_categories.get(item_name)?.member(member_id)?;
+    member_field_value(member, field_key)
+}
+
+fn member_field_value(member: &Member, field_key: &str) -> Option<Value> {
+    if field_key == "id" {
+        return Some(Value::Number(member.id.0 as i32));
+    }
+    member.fields.get(field_key).cloned()
+}
@@ -4,25 +4,87 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<String>,
 }
 
 pub enum ApiError {
     ItemNotFound,
     MemberNotFound,
+    DuplicateMemberId,
+    MemberIdsExhausted,
+    ConstraintNotFound,
+    ConstraintIdsExhausted,
+    DuplicateConstraintName,
+    CurriculumNotFound,
+    DuplicateCurriculumName,
+    PairingNotFound,
+    HasDependents(Vec<String>),
     InvalidInput,
+    InvalidSchemaChange(String),
+    InvalidConstraint(Vec<String>),
+    InvalidCurriculum(Vec<String>),
+    InvalidMember(Vec<String>),
+    InvalidPairing(Vec<String>),
+    InvalidDistances(Vec<String>),
+    InvalidDimensions(Vec<String>),
+    InvalidSlotTemplate(Vec<String>),
     UnexpectedError,
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
+        let mut details = Vec::new();
         let (status, error_message) = match self {
-            ApiError::ItemNotFound => (StatusCode::NOT_FOUND, "Item not found"),
-            ApiError::MemberNotFound => (StatusCode::NOT_FOUND, "Member not found"),
-            ApiError::InvalidInput => (StatusCode::BAD_REQUEST, "Invalid input"),
-            ApiError::UnexpectedError => (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error"),
+            ApiError::ItemNotFound => (StatusCode::NOT_FOUND, "Item not found".to_string()),
+            ApiError::MemberNotFound => (StatusCode::NOT_FOUND, "Member not found".to_string()),
+            ApiError::DuplicateMemberId => (StatusCode::CONFLICT, "Member ID already exists".to_string()),
+            ApiError::MemberIdsExhausted => (StatusCode::CONFLICT, "No member IDs left in this item".to_string()),
+            ApiError::ConstraintNotFound => (StatusCode::NOT_FOUND, "Constraint not found".to_string()),
+            ApiError::ConstraintIdsExhausted => (StatusCode::CONFLICT, "No constraint IDs left".to_string()),
+            ApiError::DuplicateConstraintName => (StatusCode::CONFLICT, "Constraint name already exists".to_string()),
+            ApiError::CurriculumNotFound => (StatusCode::NOT_FOUND, "Curriculum not found".to_string()),
+            ApiError::DuplicateCurriculumName => (StatusCode::CONFLICT, "Curriculum name already exists".to_string()),
+            ApiError::PairingNotFound => (StatusCode::NOT_FOUND, "Pairing not found".to_string()),
+            ApiError::HasDependents(dependents) => {
+                details = dependents;
+                (StatusCode::CONFLICT, "Still referenced elsewhere".to_string())
+            }
+            ApiError::InvalidInput => (StatusCode::BAD_REQUEST, "Invalid input".to_string()),
+            ApiError::InvalidSchemaChange(reason) => (StatusCode::BAD_REQUEST, reason),
+            ApiError::InvalidConstraint(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid constraint".to_string())
+            }
+            ApiError::InvalidCurriculum(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid curriculum".to_string())
+            }
+            ApiError::InvalidMember(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid member".to_string())
+            }
+            ApiError::InvalidPairing(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid pairing".to_string())
+            }
+            ApiError::InvalidDistances(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid distances".to_string())
+            }
+            ApiError::InvalidDimensions(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid dimensions".to_string())
+            }
+            ApiError::InvalidSlotTemplate(errors) => {
+                details = errors;
+                (StatusCode::BAD_REQUEST, "Invalid timeslot template".to_string())
+            }
+            ApiError::UnexpectedError => (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error".to_string()),
         };
         let body = Json(ErrorResponse {
-            error: error_message.to_string(),
+            error: error_message,
+            details,
         });
         (status, body).into_response()
     }
@@ -1,18 +1,30 @@
-use std::sync::Arc;
+use std::sync::{atomic::AtomicU32, Arc};
 use tokio::sync::RwLock;
 use crate::domain::*;
 
 pub type ProblemDataType = Arc<RwLock<ProblemData>>;
 pub type ConstraintsType = Arc<RwLock<Vec<Constraint>>>;
+pub type ScheduleType = Arc<RwLock<Option<Schedule>>>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub problem_data: ProblemDataType,
     pub constraints: ConstraintsType,
+    /// Best schedule from the most recent solve.
+    pub schedule: ScheduleType,
+    /// Highest constraint ID handed out so far, including constraints deleted
+    /// since, so that their IDs are not given out again.
+    pub last_constraint_id: Arc<AtomicU32>,
 }
 
 impl AppState {
-    pub fn new(problem_data: ProblemDataType, constraints: ConstraintsType) -> Self {
-        Self { problem_data, constraints }
+    pub fn new(problem_data: ProblemData, constraints: Vec<Constraint>, schedule: Option<Schedule>) -> Self {
+        let last_constraint_id = constraints.iter().map(|c| c.id.0).max().unwrap_or(0);
+        Self {
+            problem_data: Arc::new(RwLock::new(problem_data)),
+            constraints: Arc::new(RwLock::new(constraints)),
+            schedule: Arc::new(RwLock::new(schedule)),
+            last_constraint_id: Arc::new(AtomicU32::new(last_constraint_id)),
+        }
     }
 }
@@ -1,3 +1,4 @@
+use super::compiled::CompiledProblem;
 use crate::domain::*;
 use rand::Rng;
 use rand::seq::SliceRandom;
@@ -17,25 +18,20 @@ impl SimulatedAnnealing {
         }
     }
 
-    pub fn solve(
-        &self,
-        problem_data: &ProblemData,
-        constraints: &[Constraint],
-        initial_schedule: Schedule,
-    ) -> Schedule {
+    pub fn solve(&self, problem: &CompiledProblem, initial_schedule: Schedule) -> Schedule {
         let mut current = initial_schedule;
         let mut best = current.clone();
-        let mut current_cost = super::constraint_evaluator::evaluate_schedule(&current, problem_data, constraints);
+        let mut current_cost = problem.evaluate(&current);
         let mut best_cost = current_cost;
 
         let mut temperature = self.initial_temperature;
         let mut rng = rand::thread_rng();
 
         for iteration in 0..self.max_iterations {
-            let neighbor = self.get_neighbor(&current, problem_data, &mut rng);
-            let neighbor_cost = super::constraint_evaluator::evaluate_schedule(&neighbor, problem_data, constraints);
+            let neighbor = self.get_neighbor(&current, problem, &mut rng);
+            let neighbor_cost = problem.evaluate(&neighbor);
 
-            let delta = neighbor_cost as i32 - current_cost as i32;
+            let delta = neighbor_cost as i64 - current_cost as i64;
             if delta < 0 || self.should_accept(delta as f64, temperature, &mut rng) {
                 current = neighbor;
                 current_cost = neighbor_cost;
@@ -65,7 +61,7 @@ impl SimulatedAnnealing {
         rng.gen::<f64>() < probability
     }
 
-    fn get_neighbor(&self, schedule: &Schedule, problem_data: &ProblemData, rng: &mut impl Rng) -> Schedule {
+    fn get_neighbor(&self, schedule: &Schedule, problem: &CompiledProblem, rng: &mut impl Rng) -> Schedule {
         let mut new_schedule = schedule.clone();
 
         if new_schedule.assignments.is_empty() {
@@ -79,13 +75,12 @@ impl SimulatedAnnealing {
             let idx = rng.gen_range(0..new_schedule.assignments.len());
             let assignment = &mut new_schedule.assignments[idx];
             
-            // Pick random resource type to change
+            // Pick random resource type to change, drawing only from members paired with the task
             let resource_names: Vec<String> = assignment.resources.keys().cloned().collect();
             if let Some(resource_name) = resource_names.choose(rng) {
-                if let Some(item) = problem_data.item_categories.get(resource_name) {
-                    if let Some(new_member) = item.members.choose(rng) {
-                        assignment.resources.insert(resource_name.clone(), new_member.id);
-                    }
+                let candidates = problem.allowed_resources(&assignment.task_item_name, assignment.task_id, resource_name);
+                if let Some(new_member) = candidates.choose(rng) {
+                    assignment.resources.insert(resource_name.clone(), *new_member);
                 }
             }
 } else {
@@ -102,8 +97,14 @@ impl SimulatedAnnealing {
                 let r2 = new_schedule.assignments[idx2].resources.get(resource_name).copied();
                 
                 if let (Some(id1), Some(id2)) = (r1, r2) {
-                    new_schedule.assignments[idx1].resources.insert(resource_name.clone(), id2);
-                    new_schedule.assignments[idx2].resources.insert(resource_name.clone(), id1);
+                    // Only swap when both tasks may use the other's resource
+                    let (first, second) = (&new_schedule.assignments[idx1], &new_schedule.assignments[idx2]);
+                    let allowed = problem.is_allowed_resource(&first.task_item_name, first.task_id, resource_name, id2)
+                        && problem.is_allowed_resource(&second.task_item_name, second.task_id, resource_name, id1);
+                    if allowed {
+                        new_schedule.assignments[idx1].resources.insert(resource_name.clone(), id2);
+                        new_schedule.assignments[idx2].resources.insert(resource_name.clone(), id1);
+                    }
                 }
             }
         }
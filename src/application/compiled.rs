@@ -0,0 +1,829 @@
+use crate::domain::{time, *};
+use std::{borrow::Cow, collections::{HashMap, HashSet}};
+
+/// Read-only view of a problem and its enabled constraints, built once and
+/// then used to evaluate many schedules.
+///
+/// Items and their fields are numbered, each member's field values sit in a
+/// table found by position instead of by searching, the spans of the time
+/// item are parsed up front and every rule's `Item:field` strings and target
+/// values are resolved ahead of time.
+pub struct CompiledProblem<'a> {
+    pub data: &'a ProblemData,
+    pub(crate) constraints: Vec<CompiledConstraint<'a>>,
+    item_index: HashMap<&'a str, usize>,
+    items: Vec<CompiledItem<'a>>,
+    pub(crate) time_item: Option<usize>,
+    pub(crate) space_item: Option<usize>,
+    /// Task and resource items some pairing restricts.
+    paired_items: HashSet<(&'a str, &'a str)>,
+    /// Resources each task of `paired_items` may use, keyed by task item,
+    /// task and resource item.
+    paired: HashMap<(usize, ItemId, usize), PairedResources>,
+}
+
+struct CompiledItem<'a> {
+    item: &'a Item,
+    /// Id of every member, in member order.
+    ids: Vec<ItemId>,
+    /// Column of every field; `id` is always column 0 and never stored.
+    columns: HashMap<&'a str, usize>,
+    /// `values[position * columns.len() + column]`.
+    values: Vec<Option<&'a Value>>,
+    positions: MemberPositions,
+    /// Span of each member; only filled in for the time item.
+    spans: Vec<Option<TimeSpan>>,
+}
+
+/// Where a member sits in its item. The first member wins when ids repeat,
+/// like `Item::member`.
+enum MemberPositions {
+    /// Indexed by id, for items whose ids are small and close together.
+    Dense(Vec<Option<usize>>),
+    Sparse(HashMap<ItemId, usize>),
+}
+
+/// The resources a pairing leaves a task, in member order and as a set.
+struct PairedResources {
+    ids: Vec<ItemId>,
+    lookup: HashSet<ItemId>,
+}
+
+const ID_COLUMN: usize = 0;
+
+/// A field of an item; `column` is `None` when the item has no such field.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FieldRef {
+    pub(crate) item: usize,
+    column: Option<usize>,
+}
+
+pub(crate) struct CompiledConstraint<'a> {
+    pub(crate) constraint: &'a Constraint,
+    pub(crate) rule: CompiledRule<'a>,
+}
+
+/// A `ConstraintRule` with its item and field names resolved. A rule with an
+/// `Item:field` string lacking the colon never reports a violation.
+pub(crate) enum CompiledRule<'a> {
+    Inert,
+    MultiAssignmentCheck {
+        conditions: CompiledConditions<'a>,
+        logical_op: &'a LogicalOperator,
+        mode: &'a ConstraintMode,
+    },
+    GlobalAllDifferent {
+        unique: FieldRef,
+        group: FieldRef,
+    },
+    GlobalCardinality {
+        target: FieldRef,
+        min_count: Option<u32>,
+        max_count: Option<u32>,
+        scope: Option<CompiledConditions<'a>>,
+        groups: Vec<FieldRef>,
+        sum: Option<FieldRef>,
+        include_unused: bool,
+    },
+    GlobalTemporalPrecedence {
+        group: FieldRef,
+        first: CompiledConditions<'a>,
+        second: CompiledConditions<'a>,
+        relation: &'a ComparisonOperator,
+        /// Start and end field, or `None` when the rule names neither one
+        /// nor two fields.
+        fields: Option<(FieldRef, FieldRef)>,
+    },
+    Implication {
+        antecedent: CompiledConditions<'a>,
+        consequent: CompiledConditions<'a>,
+    },
+    PairwiseRelation {
+        first: CompiledConditions<'a>,
+        second: CompiledConditions<'a>,
+        relation: &'a PairRelation,
+    },
+    GlobalSpread {
+        group: FieldRef,
+        spread: FieldRef,
+        min_distinct: Option<u32>,
+        scope: Option<CompiledConditions<'a>>,
+    },
+    GlobalConsistency {
+        group: FieldRef,
+        resource: FieldRef,
+        max_distinct: u32,
+        scope: Option<CompiledConditions<'a>>,
+    },
+    TravelTime {
+        resource: FieldRef,
+        location: FieldRef,
+        scope: Option<CompiledConditions<'a>>,
+    },
+    GlobalBalance {
+        target: FieldRef,
+        sum: Option<FieldRef>,
+        measure: &'a BalanceMeasure,
+        scope: Option<CompiledConditions<'a>>,
+    },
+    CurriculumConflict {
+        curricula: Vec<CompiledCurriculum<'a>>,
+    },
+    ResourceAvailability {
+        level: AvailabilityLevel,
+        /// Items whose members are checked.
+        items: Vec<usize>,
+    },
+    PreferenceMatrix {
+        row: Option<usize>,
+        column: Option<usize>,
+        default_score: u32,
+        scores: HashMap<(ItemId, ItemId), u32>,
+    },
+    Compactness {
+        resource: FieldRef,
+        scope: Option<CompiledConditions<'a>>,
+        measure: &'a CompactnessMeasure,
+    },
+}
+
+pub(crate) struct CompiledCurriculum<'a> {
+    pub(crate) curriculum: &'a Curriculum,
+    pub(crate) tasks: HashSet<ItemId>,
+}
+
+pub(crate) enum CompiledConditions<'a> {
+    List(Vec<CompiledCondition<'a>>),
+    Expr(CompiledExpr<'a>),
+}
+
+pub(crate) enum CompiledExpr<'a> {
+    Condition(CompiledCondition<'a>),
+    And(Vec<CompiledExpr<'a>>),
+    Or(Vec<CompiledExpr<'a>>),
+    Not(Box<CompiledExpr<'a>>),
+    Implies {
+        antecedent: Box<CompiledExpr<'a>>,
+        consequent: Box<CompiledExpr<'a>>,
+    },
+}
+
+pub(crate) struct CompiledCondition<'a> {
+    pub(crate) field: Option<FieldRef>,
+    pub(crate) operator: &'a ComparisonOperator,
+    pub(crate) targets: Targets<'a>,
+    pub(crate) operand: Option<(Option<FieldRef>, &'a FieldOperand)>,
+}
+
+/// A condition's target values, together with those that parse as numbers,
+/// as times or as dates, and the range they span.
+pub(crate) struct Targets<'a> {
+    pub(crate) strings: &'a [String],
+    pub(crate) numbers: Vec<i32>,
+    /// Minutes since midnight of the targets that are times.
+    pub(crate) times: Vec<i64>,
+    /// Minutes since 1970-01-01 of the targets that are dates or date-times.
+    pub(crate) dated_times: Vec<i64>,
+    pub(crate) range: Option<(&'a String, &'a String)>,
+}
+
+/// When an assignment takes place: its day (a weekday name or ISO date) and
+/// start and end in minutes since midnight.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TimeSpan {
+    pub(crate) day: Option<String>,
+    pub(crate) start: u32,
+    pub(crate) end: u32,
+}
+
+impl TimeSpan {
+    pub(crate) fn overlaps(&self, other: &TimeSpan) -> bool {
+        self.day == other.day && self.start < other.end && other.start < self.end
+    }
+
+    pub(crate) fn ends_before(&self, other: &TimeSpan) -> bool {
+        if self.day == other.day {
+            return self.end <= other.start;
+        }
+        match (&self.day, &other.day) {
+            (Some(a), Some(b)) => match (time::weekday_index(a), time::weekday_index(b)) {
+                (Some(a), Some(b)) => a < b,
+                _ => a < b,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// A member an assignment uses: its id, and where it sits in its item unless
+/// the item has no member with that id.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MemberSlot {
+    id: ItemId,
+    position: Option<usize>,
+}
+
+/// An assignment with the member it uses from every item looked up once.
+pub(crate) struct AssignmentView<'s> {
+    pub(crate) assignment: &'s Assignment,
+    pub(crate) task_item: Option<usize>,
+    /// Indexed by item; the task wins over a resource of the same item.
+    members: Vec<Option<MemberSlot>>,
+}
+
+/// A field value borrowed from the problem data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ValueRef<'v> {
+    String(&'v str),
+    Number(i32),
+    Date(&'v str),
+}
+
+impl<'v> From<&'v Value> for ValueRef<'v> {
+    fn from(value: &'v Value) -> Self {
+        match value {
+            Value::String(s) => ValueRef::String(s),
+            Value::Number(n) => ValueRef::Number(*n),
+            Value::Date(d) => ValueRef::Date(d),
+        }
+    }
+}
+
+/// A value as the evaluator groups by it: text and dates by their text,
+/// missing values as empty text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ValueKey<'v> {
+    Text(&'v str),
+    Number(i32),
+}
+
+impl<'v> ValueKey<'v> {
+    pub(crate) fn of(value: Option<ValueRef<'v>>) -> Self {
+        match value {
+            Some(ValueRef::String(s)) | Some(ValueRef::Date(s)) => ValueKey::Text(s),
+            Some(ValueRef::Number(n)) => ValueKey::Number(n),
+            None => ValueKey::Text(""),
+        }
+    }
+
+    /// The key as text, e.g. to look up a location's travel times.
+    pub(crate) fn text(self) -> Cow<'v, str> {
+        match self {
+            ValueKey::Text(s) => Cow::Borrowed(s),
+            ValueKey::Number(n) => Cow::Owned(n.to_string()),
+        }
+    }
+}
+
+impl<'a> CompiledProblem<'a> {
+    pub fn new(data: &'a ProblemData, constraints: &'a [Constraint]) -> Self {
+        let mut names: Vec<&'a String> = data.item_categories.keys().collect();
+        names.sort();
+        let item_index: HashMap<&'a str, usize> = names.iter()
+            .enumerate()
+            .map(|(index, name)| (name.as_str(), index))
+            .collect();
+
+        let dimension = data.time();
+        let time_item = item_index.get(dimension.item_name.as_str()).copied();
+        let space_item = item_index.get(data.space().item_name.as_str()).copied();
+        let items = names.iter()
+            .enumerate()
+            .map(|(index, name)| compile_item(&data.item_categories[*name], (time_item == Some(index)).then_some(dimension)))
+            .collect();
+
+        let mut compiled = CompiledProblem {
+            data,
+            constraints: Vec::new(),
+            item_index,
+            items,
+            time_item,
+            space_item,
+            paired_items: data.pairings.iter()
+                .map(|p| (p.task_item.as_str(), p.resource_item.as_str()))
+                .collect(),
+            paired: HashMap::new(),
+        };
+        compiled.paired = compiled.compile_pairings();
+        compiled.constraints = constraints.iter()
+            .filter(|c| c.enabled)
+            .map(|constraint| CompiledConstraint {
+                constraint,
+                rule: compiled.compile_rule(&constraint.rule).unwrap_or(CompiledRule::Inert),
+            })
+            .collect();
+        compiled
+    }
+
+    /// Members of `resource_item` that every pairing lets the task use, like
+    /// `ProblemData::allowed_resources` but without searching the pairings.
+    pub(crate) fn allowed_resources(&self, task_item: &str, task_id: ItemId, resource_item: &str) -> Cow<'_, [ItemId]> {
+        let Some(resource) = self.item(resource_item) else {
+            return Cow::Borrowed(&[]);
+        };
+        if !self.paired_items.contains(&(task_item, resource_item)) {
+            return Cow::Borrowed(&self.items[resource].ids);
+        }
+        match self.item(task_item).and_then(|task| self.paired.get(&(task, task_id, resource))) {
+            Some(paired) => Cow::Borrowed(&paired.ids),
+            None => Cow::Owned(self.data.allowed_resources(task_item, task_id, resource_item)),
+        }
+    }
+
+    /// Whether every pairing lets the task use `resource_id`.
+    pub(crate) fn is_allowed_resource(&self, task_item: &str, task_id: ItemId, resource_item: &str, resource_id: ItemId) -> bool {
+        let Some(resource) = self.item(resource_item) else {
+            return false;
+        };
+        if !self.paired_items.contains(&(task_item, resource_item)) {
+            return self.slot(resource, resource_id).position.is_some();
+        }
+        match self.item(task_item).and_then(|task| self.paired.get(&(task, task_id, resource))) {
+            Some(paired) => paired.lookup.contains(&resource_id),
+            None => self.data.is_allowed_resource(task_item, task_id, resource_item, resource_id),
+        }
+    }
+
+    fn compile_pairings(&self) -> HashMap<(usize, ItemId, usize), PairedResources> {
+        let mut paired = HashMap::new();
+        for pairing in &self.data.pairings {
+            let (Some(task), Some(resource)) = (self.item(&pairing.task_item), self.item(&pairing.resource_item)) else {
+                continue;
+            };
+            for member in &self.items[task].item.members {
+                paired.entry((task, member.id, resource)).or_insert_with(|| {
+                    let ids = self.data.allowed_resources(&pairing.task_item, member.id, &pairing.resource_item);
+                    let lookup = ids.iter().copied().collect();
+                    PairedResources { ids, lookup }
+                });
+            }
+        }
+        paired
+    }
+
+    pub(crate) fn item(&self, name: &str) -> Option<usize> {
+        self.item_index.get(name).copied()
+    }
+
+    /// Every assignment of `schedule` with its members looked up.
+    pub(crate) fn views<'s>(&self, schedule: &'s Schedule) -> Vec<AssignmentView<'s>> {
+        schedule.assignments.iter()
+            .map(|assignment| {
+                let mut members = vec![None; self.items.len()];
+                for (name, id) in &assignment.resources {
+                    if let Some(item) = self.item(name) {
+                        members[item] = Some(self.slot(item, *id));
+                    }
+                }
+                let task_item = self.item(&assignment.task_item_name);
+                if let Some(item) = task_item {
+                    members[item] = Some(self.slot(item, assignment.task_id));
+                }
+                AssignmentView { assignment, task_item, members }
+            })
+            .collect()
+    }
+
+    fn slot(&self, item: usize, id: ItemId) -> MemberSlot {
+        let position = match &self.items[item].positions {
+            MemberPositions::Dense(positions) => positions.get(id.0 as usize).copied().flatten(),
+            MemberPositions::Sparse(positions) => positions.get(&id).copied(),
+        };
+        MemberSlot { id, position }
+    }
+
+    /// The member the assignment uses from `item`, whether it is the task
+    /// itself or one of its resources.
+    pub(crate) fn member(&self, view: &AssignmentView, item: usize) -> Option<ItemId> {
+        view.uses(item).map(|slot| slot.id)
+    }
+
+    /// The member the assignment lists as a resource of `item`, even when the
+    /// task belongs to that item too.
+    fn resource(&self, view: &AssignmentView, item: usize) -> Option<MemberSlot> {
+        if view.task_item == Some(item) {
+            let id = view.assignment.resources.get(&self.items[item].item.name)?;
+            return Some(self.slot(item, *id));
+        }
+        view.uses(item)
+    }
+
+    pub(crate) fn resource_id(&self, view: &AssignmentView, item: usize) -> Option<ItemId> {
+        self.resource(view, item).map(|slot| slot.id)
+    }
+
+    /// Availability of the member the assignment lists as a resource of
+    /// `item`, if there is such a member.
+    pub(crate) fn resource_availability(&self, view: &AssignmentView, item: usize) -> Option<&'a [AvailabilityWindow]> {
+        let position = self.resource(view, item)?.position?;
+        Some(&self.items[item].item.members[position].availability)
+    }
+
+    /// Value of `field` for the member the assignment uses from its item.
+    pub(crate) fn value(&self, view: &AssignmentView, field: FieldRef) -> Option<ValueRef<'a>> {
+        let slot = view.uses(field.item)?;
+        match field.column? {
+            ID_COLUMN => Some(ValueRef::Number(slot.id.0 as i32)),
+            column => self.stored_value(field.item, slot.position?, column),
+        }
+    }
+
+    /// Values of `field` for every member of the field's item.
+    pub(crate) fn member_values(&self, field: FieldRef) -> impl Iterator<Item = Option<ValueRef<'a>>> + '_ {
+        let members = self.items.get(field.item).map_or(&[][..], |item| &item.item.members[..]);
+        members.iter().enumerate().map(move |(position, member)| match field.column? {
+            ID_COLUMN => Some(ValueRef::Number(member.id.0 as i32)),
+            column => self.stored_value(field.item, position, column),
+        })
+    }
+
+    fn stored_value(&self, item: usize, position: usize, column: usize) -> Option<ValueRef<'a>> {
+        let item = &self.items[item];
+        item.values[position * item.columns.len() + column].map(ValueRef::from)
+    }
+
+    /// When the assignment takes place, going by its time item member.
+    pub(crate) fn span<'p>(&'p self, view: &AssignmentView) -> Option<&'p TimeSpan> {
+        let item = self.time_item?;
+        let position = view.uses(item)?.position?;
+        self.items[item].spans[position].as_ref()
+    }
+
+    /// Spans of all members of the time item.
+    pub(crate) fn slot_spans(&self) -> impl Iterator<Item = &TimeSpan> {
+        self.time_item.into_iter().flat_map(|item| self.items[item].spans.iter().flatten())
+    }
+
+    fn field(&self, item_field: &str) -> Option<FieldRef> {
+        let (item_name, field_key) = item_field.split_once(':')?;
+        Some(self.field_of(item_name, field_key).unwrap_or(UNKNOWN_FIELD))
+    }
+
+    fn field_of(&self, item_name: &str, field_key: &str) -> Option<FieldRef> {
+        let item = self.item(item_name)?;
+        Some(FieldRef { item, column: self.items[item].columns.get(field_key).copied() })
+    }
+
+    fn compile_rule(&self, rule: &'a ConstraintRule) -> Option<CompiledRule<'a>> {
+        let sum = |sum_item_field: &Option<String>| match sum_item_field {
+            Some(field) => self.field(field).map(Some),
+            None => Some(None),
+        };
+
+        Some(match rule {
+            ConstraintRule::MultiAssignmentCheck { conditions, logical_op, mode } => CompiledRule::MultiAssignmentCheck {
+                conditions: self.conditions(conditions),
+                logical_op,
+                mode,
+            },
+            ConstraintRule::GlobalAllDifferent { unique_item_field, group_item_field } => CompiledRule::GlobalAllDifferent {
+                unique: self.field(unique_item_field)?,
+                group: self.field(group_item_field)?,
+            },
+            ConstraintRule::GlobalCardinality {
+                target_item_field,
+                min_count,
+                max_count,
+                scope_conditions,
+                group_item_fields,
+                sum_item_field,
+                include_unused,
+            } => CompiledRule::GlobalCardinality {
+                target: self.field(target_item_field)?,
+                min_count: *min_count,
+                max_count: *max_count,
+                scope: self.scope(scope_conditions),
+                groups: group_item_fields.iter().map(|f| self.field(f)).collect::<Option<_>>()?,
+                sum: sum(sum_item_field)?,
+                include_unused: *include_unused,
+            },
+            ConstraintRule::GlobalTemporalPrecedence {
+                grouping_item_field,
+                first_conditions,
+                second_conditions,
+                temporal_relation,
+                temporal_fields,
+                temporal_item,
+            } => {
+                let item = temporal_item.as_deref().unwrap_or(&self.data.time().item_name);
+                let field = |key: &str| self.field_of(item, key).unwrap_or(UNKNOWN_FIELD);
+                CompiledRule::GlobalTemporalPrecedence {
+                    group: self.field(grouping_item_field)?,
+                    first: self.conditions(first_conditions),
+                    second: self.conditions(second_conditions),
+                    relation: temporal_relation,
+                    fields: match temporal_fields.as_slice() {
+                        [key] => Some((field(key), field(key))),
+                        [start, end] => Some((field(start), field(end))),
+                        _ => None,
+                    },
+                }
+            }
+            ConstraintRule::Implication { antecedent, consequent } => CompiledRule::Implication {
+                antecedent: self.conditions(antecedent),
+                consequent: self.conditions(consequent),
+            },
+            ConstraintRule::PairwiseRelation { first_conditions, second_conditions, relation } => CompiledRule::PairwiseRelation {
+                first: self.conditions(first_conditions),
+                second: self.conditions(second_conditions),
+                relation,
+            },
+            ConstraintRule::GlobalSpread { group_item_field, spread_item_field, min_distinct, scope_conditions } => CompiledRule::GlobalSpread {
+                group: self.field(group_item_field)?,
+                spread: self.field(spread_item_field)?,
+                min_distinct: *min_distinct,
+                scope: self.scope(scope_conditions),
+            },
+            ConstraintRule::GlobalConsistency { group_item_field, resource_item_field, max_distinct, scope_conditions } => CompiledRule::GlobalConsistency {
+                group: self.field(group_item_field)?,
+                resource: self.field(resource_item_field)?,
+                max_distinct: *max_distinct,
+                scope: self.scope(scope_conditions),
+            },
+            ConstraintRule::TravelTime { resource_item_field, location_item_field, scope_conditions } => CompiledRule::TravelTime {
+                resource: self.field(resource_item_field)?,
+                location: self.field(location_item_field)?,
+                scope: self.scope(scope_conditions),
+            },
+            ConstraintRule::GlobalBalance { target_item_field, sum_item_field, measure, scope_conditions } => CompiledRule::GlobalBalance {
+                target: self.field(target_item_field)?,
+                sum: sum(sum_item_field)?,
+                measure,
+                scope: self.scope(scope_conditions),
+            },
+            ConstraintRule::CurriculumConflict { curricula } => CompiledRule::CurriculumConflict {
+                curricula: self.data.curricula.iter()
+                    .filter(|c| curricula.is_empty() || curricula.contains(&c.name))
+                    .map(|curriculum| CompiledCurriculum { curriculum, tasks: curriculum.tasks.iter().copied().collect() })
+                    .collect(),
+            },
+            ConstraintRule::ResourceAvailability { level, item_names } => CompiledRule::ResourceAvailability {
+                level: *level,
+                items: (0..self.items.len())
+                    .filter(|&item| item_names.is_empty() || item_names.contains(&self.items[item].item.name))
+                    .collect(),
+            },
+            ConstraintRule::PreferenceMatrix { row_item, column_item, default_score, scores } => CompiledRule::PreferenceMatrix {
+                row: self.item(row_item),
+                column: self.item(column_item),
+                default_score: *default_score,
+                scores: scores.iter().map(|s| ((s.row_id, s.column_id), s.score)).collect(),
+            },
+            ConstraintRule::Compactness { resource_item_field, scope_conditions, measure } => CompiledRule::Compactness {
+                resource: self.field(resource_item_field)?,
+                scope: self.scope(scope_conditions),
+                measure,
+            },
+        })
+    }
+
+    fn scope(&self, conditions: &'a Option<ConditionSet>) -> Option<CompiledConditions<'a>> {
+        conditions.as_ref().map(|c| self.conditions(c))
+    }
+
+    fn conditions(&self, conditions: &'a ConditionSet) -> CompiledConditions<'a> {
+        match conditions {
+            ConditionSet::List(list) => CompiledConditions::List(list.iter().map(|c| self.condition(c)).collect()),
+            ConditionSet::Expr(expr) => CompiledConditions::Expr(self.expr(expr)),
+        }
+    }
+
+    fn expr(&self, expr: &'a ConditionExpr) -> CompiledExpr<'a> {
+        match expr {
+            ConditionExpr::Condition(condition) => CompiledExpr::Condition(self.condition(condition)),
+            ConditionExpr::And(exprs) => CompiledExpr::And(exprs.iter().map(|e| self.expr(e)).collect()),
+            ConditionExpr::Or(exprs) => CompiledExpr::Or(exprs.iter().map(|e| self.expr(e)).collect()),
+            ConditionExpr::Not(expr) => CompiledExpr::Not(Box::new(self.expr(expr))),
+            ConditionExpr::Implies { antecedent, consequent } => CompiledExpr::Implies {
+                antecedent: Box::new(self.expr(antecedent)),
+                consequent: Box::new(self.expr(consequent)),
+            },
+        }
+    }
+
+    fn condition(&self, condition: &'a Condition) -> CompiledCondition<'a> {
+        let targets = &condition.target_values;
+        let minutes: Vec<(bool, i64)> = targets.iter().filter_map(|t| time::parse_minutes(t)).collect();
+        CompiledCondition {
+            field: self.field_of(&condition.item_name, &condition.field_key),
+            operator: &condition.operator,
+            targets: Targets {
+                strings: targets,
+                numbers: targets.iter().filter_map(|t| t.parse().ok()).collect(),
+                times: minutes.iter().filter(|(dated, _)| !dated).map(|(_, m)| *m).collect(),
+                dated_times: minutes.iter().filter(|(dated, _)| *dated).map(|(_, m)| *m).collect(),
+                range: targets.iter().min().zip(targets.iter().max()),
+            },
+            operand: condition.target_field.as_ref()
+                .map(|operand| (self.field_of(&operand.item_name, &operand.field_key), operand)),
+        }
+    }
+}
+
+/// Stands in for a field of an item that does not exist; no assignment has
+/// a value for it.
+const UNKNOWN_FIELD: FieldRef = FieldRef { item: usize::MAX, column: None };
+
+impl AssignmentView<'_> {
+    fn uses(&self, item: usize) -> Option<MemberSlot> {
+        self.members.get(item).copied().flatten()
+    }
+}
+
+fn compile_item<'a>(item: &'a Item, dimension: Option<&TimeDimension>) -> CompiledItem<'a> {
+    let mut columns: HashMap<&'a str, usize> = HashMap::from([("id", ID_COLUMN)]);
+    let keys = item.schema.definitions.keys().chain(item.members.iter().flat_map(|m| m.fields.keys()));
+    for key in keys {
+        let next = columns.len();
+        columns.entry(key.as_str()).or_insert(next);
+    }
+
+    let width = columns.len();
+    let mut values = vec![None; item.members.len() * width];
+    for (position, member) in item.members.iter().enumerate() {
+        for (key, value) in &member.fields {
+            let column = columns[key.as_str()];
+            if column != ID_COLUMN {
+                values[position * width + column] = Some(value);
+            }
+        }
+    }
+
+    let max_id = item.members.iter().map(|m| m.id.0 as usize).max().unwrap_or(0);
+    let positions = if max_id <= 4 * item.members.len() + 64 {
+        let mut positions = vec![None; max_id + 1];
+        for (position, member) in item.members.iter().enumerate().rev() {
+            positions[member.id.0 as usize] = Some(position);
+        }
+        MemberPositions::Dense(positions)
+    } else {
+        let mut positions = HashMap::new();
+        for (position, member) in item.members.iter().enumerate() {
+            positions.entry(member.id).or_insert(position);
+        }
+        MemberPositions::Sparse(positions)
+    };
+
+    let spans = match dimension {
+        Some(dimension) => item.members.iter().map(|m| member_time_span(m, dimension)).collect(),
+        None => Vec::new(),
+    };
+
+    let ids = item.members.iter().map(|m| m.id).collect();
+    CompiledItem { item, ids, columns, values, positions, spans }
+}
+
+fn member_time_span(member: &Member, dimension: &TimeDimension) -> Option<TimeSpan> {
+    let value_of = |field_key: &str| {
+        if field_key == "id" {
+            return Some(Value::Number(member.id.0 as i32));
+        }
+        member.fields.get(field_key).cloned()
+    };
+
+    let start = value_of(&dimension.start_field)?;
+    let end = value_of(&dimension.end_field).unwrap_or_else(|| start.clone());
+    let (Value::Date(start), Value::Date(end)) = (start, end) else {
+        return None;
+    };
+
+    let day = match value_of(&dimension.day_field) {
+        Some(Value::String(day)) | Some(Value::Date(day)) => Some(day),
+        _ => time::parse_date(&start).map(|(y, m, d)| format!("{:04}-{:02}-{:02}", y, m, d)),
+    };
+
+    Some(TimeSpan {
+        day,
+        start: time::parse_clock(&start)?,
+        end: time::parse_clock(&end)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    #[test]
+    fn paired_resources_match_the_pairings() {
+        let mut problem_data = timetable();
+        problem_data.pairings = vec![
+            ResourcePairing {
+                task_item: "Course".to_string(),
+                resource_item: "Lecturer".to_string(),
+                rule: PairingRule::Table { allowed: vec![AllowedResources { task_id: ItemId(1), resource_ids: vec![ItemId(2)] }] },
+            },
+            ResourcePairing {
+                task_item: "Course".to_string(),
+                resource_item: "Room".to_string(),
+                rule: PairingRule::FieldMatch { task_field: "students".to_string(), resource_field: "capacity".to_string() },
+            },
+        ];
+        let compiled = CompiledProblem::new(&problem_data, &[]);
+
+        for task in 1..=5 {
+            for resource in ["Lecturer", "Room", "TimeSlot", "Missing"] {
+                let expected = problem_data.allowed_resources("Course", ItemId(task), resource);
+                assert_eq!(*compiled.allowed_resources("Course", ItemId(task), resource), expected, "course {} {}", task, resource);
+                for id in 0..=5 {
+                    assert_eq!(
+                        compiled.is_allowed_resource("Course", ItemId(task), resource, ItemId(id)),
+                        expected.contains(&ItemId(id)),
+                        "course {} {} {}", task, resource, id,
+                    );
+                }
+            }
+        }
+        assert_eq!(*compiled.allowed_resources("Course", ItemId(1), "Lecturer"), [ItemId(2)]);
+        assert_eq!(*compiled.allowed_resources("Course", ItemId(2), "Lecturer"), [ItemId(1), ItemId(2)]);
+        assert_eq!(*compiled.allowed_resources("Course", ItemId(4), "Room"), [ItemId(1)]);
+        assert!(compiled.allowed_resources("Course", ItemId(3), "Room").is_empty());
+    }
+
+    fn rooms(members: Vec<Member>) -> ProblemData {
+        let mut problem_data = timetable();
+        problem_data.item_categories.get_mut("Room").unwrap().members = members;
+        problem_data
+    }
+
+    fn capacity(id: u32, capacity: i32) -> Member {
+        member(id, &[("capacity", Value::Number(capacity))])
+    }
+
+    /// Capacity of the room each lesson of course 1 in `rooms` uses.
+    fn capacities(compiled: &CompiledProblem, rooms: &[u32]) -> Vec<Option<i32>> {
+        let schedule = Schedule::new(rooms.iter().map(|&room| lesson(1, room, 1, 1)).collect());
+        let field = compiled.field("Room:capacity").unwrap();
+        compiled.views(&schedule).iter()
+            .map(|view| match compiled.value(view, field) {
+                Some(ValueRef::Number(n)) => Some(n),
+                other => other.map(|value| panic!("capacity {:?}", value)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_member_wins_when_ids_repeat() {
+        for id in [2, 1000] {
+            let problem_data = rooms(vec![capacity(id, 20), capacity(id, 99), capacity(1, 40)]);
+            let compiled = CompiledProblem::new(&problem_data, &[]);
+            assert_eq!(capacities(&compiled, &[id, 1]), [Some(20), Some(40)], "id {}", id);
+        }
+    }
+
+    #[test]
+    fn sparse_ids_are_found_by_id() {
+        let problem_data = rooms(vec![capacity(500, 20), capacity(7, 40), capacity(90_000, 60)]);
+        let compiled = CompiledProblem::new(&problem_data, &[]);
+        let room = compiled.item("Room").unwrap();
+        assert!(matches!(compiled.items[room].positions, MemberPositions::Sparse(_)));
+        assert_eq!(
+            capacities(&compiled, &[90_000, 7, 500, 8]),
+            [Some(60), Some(40), Some(20), None],
+        );
+    }
+
+    #[test]
+    fn unknown_fields_have_no_values() {
+        let problem_data = timetable();
+        let compiled = CompiledProblem::new(&problem_data, &[]);
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1)]);
+        let view = &compiled.views(&schedule)[0];
+
+        let unknown_item = compiled.field("Nowhere:name").unwrap();
+        assert_eq!((unknown_item.item, unknown_item.column), (usize::MAX, None));
+        assert_eq!(compiled.value(view, unknown_item), None);
+        assert_eq!(compiled.member_values(unknown_item).count(), 0);
+
+        let unknown_field = compiled.field("Room:colour").unwrap();
+        assert_eq!(unknown_field.item, compiled.item("Room").unwrap());
+        assert_eq!(unknown_field.column, None);
+        assert_eq!(compiled.value(view, unknown_field), None);
+        assert!(compiled.member_values(unknown_field).all(|value| value.is_none()));
+
+        assert!(compiled.field("Room").is_none());
+    }
+
+    #[test]
+    fn missing_resource_members_keep_their_id_only() {
+        let problem_data = timetable();
+        let constraints = vec![
+            constraint(1, serde_json::json!({ "GlobalAllDifferent": {
+                "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id",
+            } })),
+            constraint(2, serde_json::json!({ "ResourceAvailability": { "level": "Hard" } })),
+        ];
+        let compiled = CompiledProblem::new(&problem_data, &constraints);
+        let schedule = Schedule::new(vec![lesson(1, 9, 1, 9), lesson(2, 9, 2, 9)]);
+        let view = &compiled.views(&schedule)[0];
+        let room = compiled.item("Room").unwrap();
+
+        assert_eq!(compiled.member(view, room), Some(ItemId(9)));
+        assert_eq!(compiled.value(view, compiled.field("Room:id").unwrap()), Some(ValueRef::Number(9)));
+        assert_eq!(compiled.value(view, compiled.field("Room:capacity").unwrap()), None);
+        assert!(compiled.resource_availability(view, room).is_none());
+        assert!(compiled.span(view).is_none());
+        // Both lessons share room 9 in slot 9
+        assert_eq!(compiled.evaluate(&schedule), 1);
+    }
+}
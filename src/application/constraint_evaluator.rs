@@ -1,400 +1,581 @@
-use crate::domain::*;
+use super::compiled::*;
+use crate::domain::{time, *};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
+/// How one enabled constraint fares on a schedule.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConstraintReport {
+    pub id: ConstraintId,
+    pub name: String,
+    /// Sum of the violation magnitudes, before the penalty function.
+    pub violations: u32,
+    /// What the constraint adds to the schedule's cost.
+    pub penalty: u32,
+}
+
+/// Cost of a schedule. Compiles the problem on every call; compile it once
+/// with `CompiledProblem::new` when evaluating many schedules.
 pub fn evaluate_schedule(
     schedule: &Schedule,
     problem_data: &ProblemData,
     constraints: &[Constraint],
 ) -> u32 {
-    let mut total_cost = 0;
-    
-    for constraint in constraints {
-        let violations = evaluate_constraint(&constraint.rule, schedule, problem_data);
-        total_cost += violations * constraint.weight;
-    }
-    
-    total_cost
+    CompiledProblem::new(problem_data, constraints).evaluate(schedule)
 }
 
-fn evaluate_constraint(
-    rule: &ConstraintRule,
+pub fn report_schedule(
     schedule: &Schedule,
     problem_data: &ProblemData,
-) -> u32 {
+    constraints: &[Constraint],
+) -> Vec<ConstraintReport> {
+    CompiledProblem::new(problem_data, constraints).report(schedule)
+}
+
+impl CompiledProblem<'_> {
+    /// Sum of the penalties of all enabled constraints; saturates at
+    /// `u32::MAX` instead of wrapping.
+    pub fn evaluate(&self, schedule: &Schedule) -> u32 {
+        let views = self.views(schedule);
+        self.constraints.iter()
+            .map(|compiled| constraint_penalty(compiled.constraint, &evaluate_constraint(&compiled.rule, &views, self)))
+            .fold(0, u32::saturating_add)
+    }
+
+    pub fn report(&self, schedule: &Schedule) -> Vec<ConstraintReport> {
+        let views = self.views(schedule);
+        self.constraints.iter()
+            .map(|compiled| {
+                let constraint = compiled.constraint;
+                let violations = evaluate_constraint(&compiled.rule, &views, self);
+                ConstraintReport {
+                    id: constraint.id,
+                    name: constraint.name.clone(),
+                    violations: violations.iter().fold(0, |total, &v| total.saturating_add(v)),
+                    penalty: constraint_penalty(constraint, &violations),
+                }
+            })
+            .collect()
+    }
+}
+
+fn constraint_penalty(constraint: &Constraint, violations: &[u32]) -> u32 {
+    let shaped = violations.iter()
+        .map(|&magnitude| constraint.penalty.apply(magnitude))
+        .fold(0, u32::saturating_add);
+    shaped.saturating_mul(constraint.weight)
+}
+
+/// Magnitude of each violation of the rule; how many there are and how
+/// large each is depends on the rule.
+fn evaluate_constraint(rule: &CompiledRule, views: &[AssignmentView], problem: &CompiledProblem) -> Vec<u32> {
     match rule {
-        ConstraintRule::MultiAssignmentCheck { conditions, logical_op, mode } => {
-            evaluate_multi_assignment(schedule, problem_data, conditions, logical_op, mode)
+        CompiledRule::Inert => Vec::new(),
+        CompiledRule::MultiAssignmentCheck { conditions, logical_op, mode } => {
+            evaluate_multi_assignment(views, problem, conditions, logical_op, mode)
+        }
+        CompiledRule::GlobalAllDifferent { unique, group } => {
+            evaluate_all_different(views, problem, *unique, *group)
+        }
+        CompiledRule::GlobalCardinality { target, min_count, max_count, scope, groups, sum, include_unused } => {
+            evaluate_cardinality(views, problem, *target, (*min_count, *max_count), scope, groups, *sum, *include_unused)
+        }
+        CompiledRule::GlobalTemporalPrecedence { group, first, second, relation, fields } => {
+            evaluate_temporal_precedence(views, problem, *group, first, second, relation, *fields)
+        }
+        CompiledRule::Implication { antecedent, consequent } => {
+            evaluate_implication(views, problem, antecedent, consequent)
         }
-        ConstraintRule::GlobalAllDifferent { unique_item_field, group_item_field } => {
-            evaluate_all_different(schedule, problem_data, unique_item_field, group_item_field)
+        CompiledRule::PairwiseRelation { first, second, relation } => {
+            evaluate_pairwise(views, problem, first, second, relation)
         }
-        ConstraintRule::GlobalCardinality { target_item_field, max_count, scope_conditions } => {
-            evaluate_cardinality(schedule, problem_data, target_item_field, *max_count, scope_conditions)
+        CompiledRule::GlobalSpread { group, spread, min_distinct, scope } => {
+            evaluate_spread(views, problem, *group, *spread, *min_distinct, scope)
         }
-        ConstraintRule::GlobalTemporalPrecedence { 
-            grouping_item_field, 
-            first_conditions, 
-            second_conditions, 
-            temporal_relation, 
-            temporal_fields 
-        } => {
-            evaluate_temporal_precedence(
-                schedule, 
-                problem_data, 
-                grouping_item_field, 
-                first_conditions, 
-                second_conditions, 
-                temporal_relation, 
-                temporal_fields
-            )
+        CompiledRule::GlobalConsistency { group, resource, max_distinct, scope } => {
+            evaluate_consistency(views, problem, *group, *resource, *max_distinct, scope)
+        }
+        CompiledRule::TravelTime { resource, location, scope } => {
+            evaluate_travel_time(views, problem, *resource, *location, scope)
+        }
+        CompiledRule::GlobalBalance { target, sum, measure, scope } => {
+            evaluate_balance(views, problem, *target, *sum, measure, scope)
+        }
+        CompiledRule::CurriculumConflict { curricula } => {
+            evaluate_curriculum_conflict(views, problem, curricula)
+        }
+        CompiledRule::ResourceAvailability { level, items } => {
+            evaluate_availability(views, problem, *level, items)
+        }
+        CompiledRule::PreferenceMatrix { row, column, default_score, scores } => {
+            evaluate_preferences(views, problem, (*row, *column), *default_score, scores)
+        }
+        CompiledRule::Compactness { resource, scope, measure } => {
+            evaluate_compactness(views, problem, *resource, scope, measure)
         }
     }
 }
 
 fn evaluate_multi_assignment(
-    schedule: &Schedule,
-    problem_data: &ProblemData,
-    conditions: &[Condition],
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    conditions: &CompiledConditions,
     logical_op: &LogicalOperator,
     mode: &ConstraintMode,
-) -> u32 {
-    let mut violations = 0;
-    
-    for assignment in &schedule.assignments {
-        let results: Vec<bool> = conditions.iter()
-            .map(|c| evaluate_condition(c, assignment, problem_data))
-            .collect();
-        
-        let combined = match logical_op {
-            LogicalOperator::And => results.iter().all(|&b| b),
-            LogicalOperator::Or => results.iter().any(|&b| b),
-        };
-        
+) -> Vec<u32> {
+    let mut violations = Vec::new();
+
+    for view in views {
+        let combined = evaluate_condition_set(conditions, logical_op, view, problem);
+
         match mode {
-            ConstraintMode::Forbid if combined => violations += 1,
-            ConstraintMode::Require if !combined => violations += 1,
+            ConstraintMode::Forbid if combined => violations.push(1),
+            ConstraintMode::Require if !combined => violations.push(1),
             _ => {}
         }
     }
-    
+
     violations
 }
 
-fn evaluate_condition(
-    condition: &Condition,
-    assignment: &Assignment,
-    problem_data: &ProblemData,
+fn evaluate_implication(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    antecedent: &CompiledConditions,
+    consequent: &CompiledConditions,
+) -> Vec<u32> {
+    views.iter()
+        .filter(|v| matches_filter(antecedent, v, problem) && !matches_filter(consequent, v, problem))
+        .map(|_| 1)
+        .collect()
+}
+
+fn evaluate_condition_set(
+    conditions: &CompiledConditions,
+    logical_op: &LogicalOperator,
+    view: &AssignmentView,
+    problem: &CompiledProblem,
 ) -> bool {
-    let member_id = if condition.item_name == assignment.task_item_name {
-        assignment.task_id
-    } else {
-        match assignment.resources.get(&condition.item_name) {
-            Some(&id) => id,
-            None => return false,
+    match conditions {
+        CompiledConditions::List(list) => match logical_op {
+            LogicalOperator::And => list.iter().all(|c| evaluate_condition(c, view, problem)),
+            LogicalOperator::Or => list.iter().any(|c| evaluate_condition(c, view, problem)),
+        },
+        CompiledConditions::Expr(expr) => evaluate_expr(expr, view, problem),
+    }
+}
+
+fn evaluate_expr(expr: &CompiledExpr, view: &AssignmentView, problem: &CompiledProblem) -> bool {
+    match expr {
+        CompiledExpr::Condition(condition) => evaluate_condition(condition, view, problem),
+        CompiledExpr::And(exprs) => exprs.iter().all(|e| evaluate_expr(e, view, problem)),
+        CompiledExpr::Or(exprs) => exprs.iter().any(|e| evaluate_expr(e, view, problem)),
+        CompiledExpr::Not(expr) => !evaluate_expr(expr, view, problem),
+        CompiledExpr::Implies { antecedent, consequent } => {
+            !evaluate_expr(antecedent, view, problem) || evaluate_expr(consequent, view, problem)
         }
+    }
+}
+
+/// Whether the assignment satisfies a rule's filter conditions, which are
+/// always combined with AND when given as a list.
+fn matches_filter(conditions: &CompiledConditions, view: &AssignmentView, problem: &CompiledProblem) -> bool {
+    evaluate_condition_set(conditions, &LogicalOperator::And, view, problem)
+}
+
+/// Whether the assignment is in the scope the conditions describe; every
+/// assignment is when there are none.
+fn in_scope(scope: &Option<CompiledConditions>, view: &AssignmentView, problem: &CompiledProblem) -> bool {
+    scope.as_ref().is_none_or(|c| matches_filter(c, view, problem))
+}
+
+fn evaluate_condition(
+    condition: &CompiledCondition,
+    view: &AssignmentView,
+    problem: &CompiledProblem,
+) -> bool {
+    let Some(value) = condition.field.and_then(|field| problem.value(view, field)) else {
+        return false;
     };
-    
-    let item = match problem_data.item_categories.get(&condition.item_name) {
-        Some(i) => i,
-        None => return false,
-    };
-    
-    let member = match item.members.iter().find(|m| m.id == member_id) {
-        Some(m) => m,
-        None => return false,
-    };
-    
-    let value = if condition.field_key == "id" {
-        Value::Number(member.id.0 as i32)
-    } else {
-        match member.fields.get(&condition.field_key) {
-            Some(v) => v.clone(),
-            None => return false,
+
+    match condition.operand {
+        Some((other, operand)) => {
+            let Some(other) = other.and_then(|field| problem.value(view, field)) else {
+                return false;
+            };
+            compare_with_operand(value, condition.operator, other, operand)
         }
-    };
-    
-    compare_value(&value, &condition.operator, &condition.target_values)
+        None => compare_value(value, condition.operator, &condition.targets),
+    }
+}
+
+/// Compares `value` against another field of the same assignment, after
+/// applying the operand's multiplier and offset. Offsets on DateTime fields
+/// are minutes.
+fn compare_with_operand(
+    value: ValueRef,
+    operator: &ComparisonOperator,
+    other: ValueRef,
+    operand: &FieldOperand,
+) -> bool {
+    match (value, other) {
+        (ValueRef::Number(n), ValueRef::Number(m)) => {
+            let target = m as f64 * operand.multiplier.unwrap_or(1.0) + operand.offset.unwrap_or(0) as f64;
+            compare_ordered(&(n as f64), operator, &target)
+        }
+        // Times and dates compare by when they are, not as text, which
+        // would put "9:00" after "13:00"
+        (ValueRef::Date(a), ValueRef::Date(b)) => match (time::parse_minutes(a), time::parse_minutes(b)) {
+            (Some((a_dated, a)), Some((b_dated, b))) if a_dated == b_dated => {
+                compare_ordered(&a, operator, &(b + operand.offset.unwrap_or(0) as i64))
+            }
+            _ => false,
+        },
+        (ValueRef::String(s), ValueRef::String(t)) => compare_text(s, operator, &[t], (t, t)),
+        _ => false,
+    }
+}
+
+fn compare_ordered<T: PartialOrd>(value: &T, operator: &ComparisonOperator, target: &T) -> bool {
+    match operator {
+        ComparisonOperator::Equal | ComparisonOperator::In => value == target,
+        ComparisonOperator::NotEqual | ComparisonOperator::NotIn => value != target,
+        ComparisonOperator::GreaterThan | ComparisonOperator::After => value > target,
+        ComparisonOperator::GreaterThanOrEqual => value >= target,
+        ComparisonOperator::LessThan | ComparisonOperator::Before => value < target,
+        ComparisonOperator::LessThanOrEqual => value <= target,
+        ComparisonOperator::Overlap | ComparisonOperator::NoOverlap => false,
+    }
 }
 
-fn compare_value(value: &Value, operator: &ComparisonOperator, targets: &[String]) -> bool {
-    match (value, operator) {
+fn compare_value(value: ValueRef, operator: &ComparisonOperator, targets: &Targets) -> bool {
+    match value {
         // ---------- Numbers ----------
-        (Value::Number(n), op) => {
-            // parse all targets as i32 (ignore ones that fail to parse)
-            let parsed: Vec<i32> = targets
-                .iter()
-                .filter_map(|t| t.parse::<i32>().ok())
-                .collect();
+        ValueRef::Number(n) => {
+            // only the targets that parse as i32 take part
+            let parsed = &targets.numbers;
 
             if parsed.is_empty() {
                 return false;
             }
 
-            match op {
-                ComparisonOperator::Equal => parsed.iter().any(|t| n == t),
-                ComparisonOperator::NotEqual => parsed.iter().all(|t| n != t),
-                ComparisonOperator::In => parsed.iter().any(|t| n == t),
-                ComparisonOperator::NotIn => parsed.iter().all(|t| n != t),
+            match operator {
+                ComparisonOperator::Equal => parsed.contains(&n),
+                ComparisonOperator::NotEqual => parsed.iter().all(|&t| n != t),
+                ComparisonOperator::In => parsed.contains(&n),
+                ComparisonOperator::NotIn => parsed.iter().all(|&t| n != t),
 
-                ComparisonOperator::GreaterThan => parsed.iter().any(|t| n > t),
-                ComparisonOperator::GreaterThanOrEqual => parsed.iter().any(|t| n >= t),
-                ComparisonOperator::LessThan => parsed.iter().any(|t| n < t),
-                ComparisonOperator::LessThanOrEqual => parsed.iter().any(|t| n <= t),
+                ComparisonOperator::GreaterThan => parsed.iter().any(|&t| n > t),
+                ComparisonOperator::GreaterThanOrEqual => parsed.iter().any(|&t| n >= t),
+                ComparisonOperator::LessThan => parsed.iter().any(|&t| n < t),
+                ComparisonOperator::LessThanOrEqual => parsed.iter().any(|&t| n <= t),
 
                 // Before / After / Overlap / NoOverlap don't have a useful numeric meaning here
                 _ => false,
             }
         }
 
+        // ---------- Times and dates, by when they are ----------
+        ValueRef::Date(s) => match time::parse_minutes(s) {
+            Some((true, minutes)) => compare_times(minutes, operator, &targets.dated_times),
+            Some((false, minutes)) => compare_times(minutes, operator, &targets.times),
+            None => compare_targets_as_text(s, operator, targets),
+        },
+
         // ---------- Strings ----------
-        (Value::String(s), op) => {
-            if targets.is_empty() {
-                return false;
-            }
+        ValueRef::String(s) => compare_targets_as_text(s, operator, targets),
+    }
+}
 
-            match op {
-                ComparisonOperator::Equal => targets.iter().any(|t| s == t),
-                ComparisonOperator::NotEqual => targets.iter().all(|t| s != t),
-                ComparisonOperator::In => targets.iter().any(|t| s == t),
-                ComparisonOperator::NotIn => targets.iter().all(|t| s != t),
-
-                // Lexicographic comparisons
-                ComparisonOperator::GreaterThan => targets.iter().any(|t| s > t),
-                ComparisonOperator::GreaterThanOrEqual => targets.iter().any(|t| s >= t),
-                ComparisonOperator::LessThan => targets.iter().any(|t| s < t),
-                ComparisonOperator::LessThanOrEqual => targets.iter().any(|t| s <= t),
-
-                // Temporal-ish operators on plain strings: treat as lexicographic dates/times if caller uses them
-                ComparisonOperator::Before => targets.iter().any(|t| s < t),
-                ComparisonOperator::After => targets.iter().any(|t| s > t),
-
-                // Overlap / NoOverlap on strings: treat targets as a range [min, max]
-                ComparisonOperator::Overlap => {
-                    let min = targets.iter().min().unwrap();
-                    let max = targets.iter().max().unwrap();
-                    s >= min && s <= max
-                }
-                ComparisonOperator::NoOverlap => {
-                    let min = targets.iter().min().unwrap();
-                    let max = targets.iter().max().unwrap();
-                    s < min || s > max
-                }
-            }
-        }
+fn compare_targets_as_text(s: &str, operator: &ComparisonOperator, targets: &Targets) -> bool {
+    match targets.range {
+        Some((min, max)) => compare_text(s, operator, targets.strings, (min, max)),
+        None => false,
+    }
+}
 
-        // ---------- Dates (stored as String, e.g. "13:30" or ISO) ----------
-        (Value::Date(d), op) => {
-            if targets.is_empty() {
-                return false;
-            }
+/// Compares text lexicographically; `range` is the smallest and largest
+/// target, which Overlap and NoOverlap treat as an interval.
+fn compare_text<T: AsRef<str>>(s: &str, operator: &ComparisonOperator, targets: &[T], (min, max): (&str, &str)) -> bool {
+    let mut targets = targets.iter().map(|t| t.as_ref());
+    match operator {
+        ComparisonOperator::Equal => targets.any(|t| s == t),
+        ComparisonOperator::NotEqual => targets.all(|t| s != t),
+        ComparisonOperator::In => targets.any(|t| s == t),
+        ComparisonOperator::NotIn => targets.all(|t| s != t),
 
-            match op {
-                ComparisonOperator::Equal => targets.iter().any(|t| d == t),
-                ComparisonOperator::NotEqual => targets.iter().all(|t| d != t),
-                ComparisonOperator::In => targets.iter().any(|t| d == t),
-                ComparisonOperator::NotIn => targets.iter().all(|t| d != t),
-
-                ComparisonOperator::GreaterThan => targets.iter().any(|t| d > t),
-                ComparisonOperator::GreaterThanOrEqual => targets.iter().any(|t| d >= t),
-                ComparisonOperator::LessThan => targets.iter().any(|t| d < t),
-                ComparisonOperator::LessThanOrEqual => targets.iter().any(|t| d <= t),
-
-                // For scalar dates, Before / After are just < and >
-                ComparisonOperator::Before => targets.iter().any(|t| d < t),
-                ComparisonOperator::After => targets.iter().any(|t| d > t),
-
-                // Overlap / NoOverlap: treat targets as an interval [min, max]
-                ComparisonOperator::Overlap => {
-                    let min = targets.iter().min().unwrap();
-                    let max = targets.iter().max().unwrap();
-                    d >= min && d <= max
-                }
-                ComparisonOperator::NoOverlap => {
-                    let min = targets.iter().min().unwrap();
-                    let max = targets.iter().max().unwrap();
-                    d < min || d > max
-                }
-            }
-        }
+        ComparisonOperator::GreaterThan => targets.any(|t| s > t),
+        ComparisonOperator::GreaterThanOrEqual => targets.any(|t| s >= t),
+        ComparisonOperator::LessThan => targets.any(|t| s < t),
+        ComparisonOperator::LessThanOrEqual => targets.any(|t| s <= t),
 
-        // Any other combination is unsupported
-        _ => false,
+        // Before / After are just < and >, as for dates and times
+        ComparisonOperator::Before => targets.any(|t| s < t),
+        ComparisonOperator::After => targets.any(|t| s > t),
+
+        ComparisonOperator::Overlap => s >= min && s <= max,
+        ComparisonOperator::NoOverlap => s < min || s > max,
     }
 }
 
+/// Compares minutes with the targets of the same kind, times with times and
+/// dates with dates; Overlap and NoOverlap span the earliest to the latest.
+fn compare_times(minutes: i64, operator: &ComparisonOperator, targets: &[i64]) -> bool {
+    let (Some(&min), Some(&max)) = (targets.iter().min(), targets.iter().max()) else {
+        return false;
+    };
+    match operator {
+        ComparisonOperator::NotEqual | ComparisonOperator::NotIn => targets.iter().all(|&t| minutes != t),
+        ComparisonOperator::Overlap => (min..=max).contains(&minutes),
+        ComparisonOperator::NoOverlap => !(min..=max).contains(&minutes),
+        _ => targets.iter().any(|t| compare_ordered(&minutes, operator, t)),
+    }
+}
 
 fn evaluate_all_different(
-    schedule: &Schedule,
-    problem_data: &ProblemData,
-    unique_item_field: &str,
-    group_item_field: &str,
-) -> u32 {
-    let (unique_item, unique_field) = parse_item_field(unique_item_field);
-    let (group_item, group_field) = parse_item_field(group_item_field);
-    
-    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
-    
-    for assignment in &schedule.assignments {
-        let group_value = extract_field_value(assignment, problem_data, &group_item, &group_field);
-        let unique_value = extract_field_value(assignment, problem_data, &unique_item, &unique_field);
-        
-        groups.entry(group_value).or_default().push(unique_value);
-    }
-    
-    let mut violations = 0;
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    unique: FieldRef,
+    group: FieldRef,
+) -> Vec<u32> {
+    let groups = group_values(views, problem, group, unique, &None);
+
+    let mut violations = Vec::new();
     for values in groups.values() {
         let unique_count = values.iter().collect::<HashSet<_>>().len();
         if unique_count < values.len() {
-            violations += (values.len() - unique_count) as u32;
+            violations.push((values.len() - unique_count) as u32);
         }
     }
-    
+
+    violations
+}
+
+fn evaluate_spread(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    group: FieldRef,
+    spread: FieldRef,
+    min_distinct: Option<u32>,
+    scope: &Option<CompiledConditions>,
+) -> Vec<u32> {
+    let groups = group_values(views, problem, group, spread, scope);
+
+    let mut violations = Vec::new();
+    for values in groups.values() {
+        let distinct = values.iter().collect::<HashSet<_>>().len() as u32;
+        let wanted = match min_distinct {
+            Some(min_distinct) => min_distinct.min(values.len() as u32),
+            None => values.len() as u32,
+        };
+        violations.push(wanted.saturating_sub(distinct));
+    }
+
     violations
 }
 
+fn evaluate_consistency(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    group: FieldRef,
+    resource: FieldRef,
+    max_distinct: u32,
+    scope: &Option<CompiledConditions>,
+) -> Vec<u32> {
+    let groups = group_values(views, problem, group, resource, scope);
+
+    groups.values()
+        .map(|values| (values.iter().collect::<HashSet<_>>().len() as u32).saturating_sub(max_distinct))
+        .collect()
+}
+
+/// Values of `value_field` for the assignments in scope, grouped by their
+/// value of `group_field`. Assignments missing either value, e.g. a task
+/// left without a resource, are left out.
+fn group_values<'a>(
+    views: &[AssignmentView],
+    problem: &CompiledProblem<'a>,
+    group_field: FieldRef,
+    value_field: FieldRef,
+    scope: &Option<CompiledConditions>,
+) -> HashMap<ValueKey<'a>, Vec<ValueKey<'a>>> {
+    let mut groups: HashMap<ValueKey, Vec<ValueKey>> = HashMap::new();
+
+    for view in views.iter().filter(|v| in_scope(scope, v, problem)) {
+        let (Some(group_value), Some(value)) = (problem.value(view, group_field), problem.value(view, value_field)) else {
+            continue;
+        };
+
+        groups.entry(ValueKey::of(Some(group_value))).or_default().push(ValueKey::of(Some(value)));
+    }
+
+    groups
+}
+
+#[allow(clippy::too_many_arguments)]
 fn evaluate_cardinality(
-    schedule: &Schedule,
-    problem_data: &ProblemData,
-    target_item_field: &str,
-    max_count: u32,
-    scope_conditions: &Option<Vec<Condition>>,
-) -> u32 {
-    let (target_item, target_field) = parse_item_field(target_item_field);
-    
-    let filtered: Vec<&Assignment> = if let Some(conditions) = scope_conditions {
-        schedule.assignments.iter()
-            .filter(|a| {
-                conditions.iter().all(|c| evaluate_condition(c, a, problem_data))
-            })
-            .collect()
-    } else {
-        schedule.assignments.iter().collect()
-    };
-    
-    let mut counts: HashMap<String, u32> = HashMap::new();
-    for assignment in filtered {
-        let value = extract_field_value(assignment, problem_data, &target_item, &target_field);
-        *counts.entry(value).or_default() += 1;
-    }
-    
-    let mut violations = 0;
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    target: FieldRef,
+    (min_count, max_count): (Option<u32>, Option<u32>),
+    scope: &Option<CompiledConditions>,
+    groups: &[FieldRef],
+    sum: Option<FieldRef>,
+    include_unused: bool,
+) -> Vec<u32> {
+    let mut counts: HashMap<(ValueKey, Vec<ValueKey>), u32> = HashMap::new();
+    for view in views.iter().filter(|v| in_scope(scope, v, problem)) {
+        let value = ValueKey::of(problem.value(view, target));
+        let group_values: Vec<ValueKey> = groups.iter()
+            .map(|field| ValueKey::of(problem.value(view, *field)))
+            .collect();
+        let count = counts.entry((value, group_values)).or_default();
+        *count = count.saturating_add(assignment_amount(view, problem, sum));
+    }
+
+    if include_unused {
+        let mut group_keys: HashSet<Vec<ValueKey>> = counts.keys().map(|(_, g)| g.clone()).collect();
+        if group_keys.is_empty() && groups.is_empty() {
+            group_keys.insert(Vec::new());
+        }
+        for value in problem.member_values(target).flatten() {
+            for group_key in &group_keys {
+                counts.entry((ValueKey::of(Some(value)), group_key.clone())).or_default();
+            }
+        }
+    }
+
+    let mut violations = Vec::new();
     for count in counts.values() {
-        if *count > max_count {
-            violations += count - max_count;
+        if let Some(max_count) = max_count {
+            violations.push(count.saturating_sub(max_count));
+        }
+        if let Some(min_count) = min_count {
+            violations.push(min_count.saturating_sub(*count));
         }
     }
-    
+
     violations
 }
 
+fn evaluate_balance(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    target: FieldRef,
+    sum: Option<FieldRef>,
+    measure: &BalanceMeasure,
+    scope: &Option<CompiledConditions>,
+) -> Vec<u32> {
+    // Every member with a value takes part, including those with nothing
+    // assigned
+    let mut totals: HashMap<ValueKey, u32> = problem.member_values(target)
+        .flatten()
+        .map(|value| (ValueKey::of(Some(value)), 0))
+        .collect();
+    for view in views.iter().filter(|v| in_scope(scope, v, problem)) {
+        let Some(value) = problem.value(view, target) else {
+            continue;
+        };
+        let total = totals.entry(ValueKey::of(Some(value))).or_default();
+        *total = total.saturating_add(assignment_amount(view, problem, sum));
+    }
+
+    if totals.is_empty() {
+        return Vec::new();
+    }
+    let values: Vec<f64> = totals.values().map(|&t| t as f64).collect();
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let deviation = match measure {
+        BalanceMeasure::Range => {
+            let max = values.iter().cloned().fold(f64::MIN, f64::max);
+            let min = values.iter().cloned().fold(f64::MAX, f64::min);
+            max - min
+        }
+        BalanceMeasure::Variance => values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64,
+        BalanceMeasure::AbsoluteDeviation => values.iter().map(|v| (v - mean).abs()).sum(),
+    };
+
+    vec![deviation.round() as u32]
+}
+
+/// What an assignment adds to a total: one, or the value of the Integer
+/// field `sum` when given.
+fn assignment_amount(view: &AssignmentView, problem: &CompiledProblem, sum: Option<FieldRef>) -> u32 {
+    match sum {
+        Some(field) => match problem.value(view, field) {
+            Some(ValueRef::Number(n)) => n.max(0) as u32,
+            _ => 0,
+        },
+        None => 1,
+    }
+}
+
 fn evaluate_temporal_precedence(
-    schedule: &Schedule,
-    problem_data: &ProblemData,
-    grouping_item_field: &str,
-    first_conditions: &[Condition],
-    second_conditions: &[Condition],
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    group: FieldRef,
+    first_conditions: &CompiledConditions,
+    second_conditions: &CompiledConditions,
     temporal_relation: &ComparisonOperator,
-    temporal_fields: &[String],
-) -> u32 {
-    let (group_item, group_field) = parse_item_field(grouping_item_field);
-    
-    let mut groups: HashMap<String, Vec<&Assignment>> = HashMap::new();
-    for assignment in &schedule.assignments {
-        let group_value = extract_field_value(assignment, problem_data, &group_item, &group_field);
-        groups.entry(group_value).or_default().push(assignment);
-    }
-    
-    let mut violations = 0;
-    for group_assignments in groups.values() {
-        let firsts: Vec<&&Assignment> = group_assignments.iter()
-            .filter(|a| first_conditions.iter().all(|c| evaluate_condition(c, a, problem_data)))
+    temporal_fields: Option<(FieldRef, FieldRef)>,
+) -> Vec<u32> {
+    let mut groups: HashMap<ValueKey, Vec<&AssignmentView>> = HashMap::new();
+    for view in views {
+        groups.entry(ValueKey::of(problem.value(view, group))).or_default().push(view);
+    }
+
+    let mut violations = Vec::new();
+    for group_views in groups.values() {
+        let firsts: Vec<&&AssignmentView> = group_views.iter()
+            .filter(|v| matches_filter(first_conditions, v, problem))
             .collect();
-        
-        let seconds: Vec<&&Assignment> = group_assignments.iter()
-            .filter(|a| second_conditions.iter().all(|c| evaluate_condition(c, a, problem_data)))
+
+        let seconds: Vec<&&AssignmentView> = group_views.iter()
+            .filter(|v| matches_filter(second_conditions, v, problem))
             .collect();
-        
+
         for first in &firsts {
             for second in &seconds {
-                if !check_temporal_relation(first, second, problem_data, temporal_relation, temporal_fields) {
-                    violations += 1;
+                if !check_temporal_relation(first, second, problem, temporal_relation, temporal_fields) {
+                    violations.push(1);
                 }
             }
         }
     }
-    
+
     violations
 }
 
 fn check_temporal_relation(
-    first: &Assignment,
-    second: &Assignment,
-    problem_data: &ProblemData,
+    first: &AssignmentView,
+    second: &AssignmentView,
+    problem: &CompiledProblem,
     relation: &ComparisonOperator,
-    temporal_fields: &[String],
+    temporal_fields: Option<(FieldRef, FieldRef)>,
 ) -> bool {
-    if temporal_fields.is_empty() || temporal_fields.len() > 2 {
+    let Some((start_field, end_field)) = temporal_fields else {
         return false;
-    }
-
-    let first_time_id = match first.resources.get("TimeSlot") {
-        Some(&id) => id,
-        None => return false,
-    };
-    let second_time_id = match second.resources.get("TimeSlot") {
-        Some(&id) => id,
-        None => return false,
     };
-
-    let time_item = match problem_data.item_categories.get("TimeSlot") {
-        Some(i) => i,
-        None => return false,
+    // Times compare by when they are, not as text, which would put "9:00"
+    // after "13:00"
+    let minutes = |view: &AssignmentView, field: FieldRef| match problem.value(view, field) {
+        Some(ValueRef::Date(v)) => time::parse_minutes(v),
+        _ => None,
     };
 
-    let first_member = match time_item.members.iter().find(|m| m.id == first_time_id) {
-        Some(m) => m,
-        None => return false,
-    };
-    let second_member = match time_item.members.iter().find(|m| m.id == second_time_id) {
-        Some(m) => m,
-        None => return false,
-    };
-
-    // Extract start/end strings
-    let (first_start, first_end, second_start, second_end) = if temporal_fields.len() == 1 {
-        let field = &temporal_fields[0];
-        let fs = match first_member.fields.get(field) {
-            Some(Value::Date(v)) => v.as_str(),
-            _ => return false,
-        };
-        let ss = match second_member.fields.get(field) {
-            Some(Value::Date(v)) => v.as_str(),
-            _ => return false,
-        };
-        (fs, fs, ss, ss)
-    } else {
-        let start_field = &temporal_fields[0];
-        let end_field = &temporal_fields[1];
-
-        let fs = match first_member.fields.get(start_field) {
-            Some(Value::Date(v)) => v.as_str(),
-            _ => return false,
-        };
-        let fe = match first_member.fields.get(end_field) {
-            Some(Value::Date(v)) => v.as_str(),
-            _ => return false,
-        };
-        let ss = match second_member.fields.get(start_field) {
-            Some(Value::Date(v)) => v.as_str(),
-            _ => return false,
-        };
-        let se = match second_member.fields.get(end_field) {
-            Some(Value::Date(v)) => v.as_str(),
-            _ => return false,
-        };
-
-        (fs, fe, ss, se)
+    let (Some((dated, first_start)), Some(first_end), Some(second_start), Some(second_end)) = (
+        minutes(first, start_field),
+        minutes(first, end_field),
+        minutes(second, start_field),
+        minutes(second, end_field),
+    ) else {
+        return false;
     };
+    // A time of day has no order against a date
+    if [first_end, second_start, second_end].iter().any(|(d, _)| *d != dated) {
+        return false;
+    }
+    let ((_, first_end), (_, second_start), (_, second_end)) = (first_end, second_start, second_end);
 
     match relation {
         ComparisonOperator::Before => first_end < second_start,
@@ -413,44 +594,741 @@ fn check_temporal_relation(
     }
 }
 
-fn parse_item_field(item_field: &str) -> (String, String) {
-    let parts: Vec<&str> = item_field.split(':').collect();
-    (parts[0].to_string(), parts[1].to_string())
+fn evaluate_pairwise(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    first_conditions: &CompiledConditions,
+    second_conditions: &CompiledConditions,
+    relation: &PairRelation,
+) -> Vec<u32> {
+    let firsts: Vec<(usize, &AssignmentView)> = views.iter().enumerate()
+        .filter(|(_, v)| matches_filter(first_conditions, v, problem))
+        .collect();
+    let seconds: Vec<(usize, &AssignmentView)> = views.iter().enumerate()
+        .filter(|(_, v)| matches_filter(second_conditions, v, problem))
+        .collect();
+
+    // Relations other than Before and After read the same either way round,
+    // so a pair matching both filters both ways is only checked once
+    let symmetric = !matches!(relation, PairRelation::Before | PairRelation::After);
+    let mut is_first = vec![false; views.len()];
+    let mut is_second = vec![false; views.len()];
+    firsts.iter().for_each(|(i, _)| is_first[*i] = true);
+    seconds.iter().for_each(|(j, _)| is_second[*j] = true);
+
+    let mut violations = Vec::new();
+    for (i, first) in &firsts {
+        for (j, second) in &seconds {
+            let mirrored = symmetric && j < i && is_first[*j] && is_second[*i];
+            if i != j && !mirrored && !check_pair_relation(first, second, problem, relation) {
+                violations.push(1);
+            }
+        }
+    }
+
+    violations
 }
 
-fn extract_field_value(
-    assignment: &Assignment,
-    problem_data: &ProblemData,
-    item_name: &str,
-    field_key: &str,
-) -> String {
-    let member_id = if item_name == assignment.task_item_name {
-        assignment.task_id
-    } else {
-        match assignment.resources.get(item_name) {
-            Some(&id) => id,
-            None => return String::new(),
+fn check_pair_relation(
+    first: &AssignmentView,
+    second: &AssignmentView,
+    problem: &CompiledProblem,
+    relation: &PairRelation,
+) -> bool {
+    let room = |view| problem.space_item.and_then(|item| problem.resource_id(view, item));
+    let rooms = || (room(first), room(second));
+    let spans = || (problem.span(first), problem.span(second));
+
+    match relation {
+        PairRelation::SameRoom => matches!(rooms(), (Some(a), Some(b)) if a == b),
+        PairRelation::DifferentRoom => matches!(rooms(), (Some(a), Some(b)) if a != b),
+        PairRelation::SameTime => matches!(spans(), (Some(a), Some(b)) if a.overlaps(b)),
+        PairRelation::DifferentTime => matches!(spans(), (Some(a), Some(b)) if !a.overlaps(b)),
+        PairRelation::SameDay => matches!(spans(), (Some(a), Some(b)) if a.day == b.day),
+        PairRelation::DifferentDay => matches!(spans(), (Some(a), Some(b)) if a.day != b.day),
+        PairRelation::MinutesApart(minutes) => matches!(
+            spans(),
+            (Some(a), Some(b)) if a.day != b.day || b.start >= a.end + minutes || a.start >= b.end + minutes
+        ),
+        PairRelation::Before => matches!(spans(), (Some(a), Some(b)) if a.ends_before(b)),
+        PairRelation::After => matches!(spans(), (Some(a), Some(b)) if b.ends_before(a)),
+    }
+}
+
+fn evaluate_travel_time(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    resource: FieldRef,
+    location: FieldRef,
+    scope: &Option<CompiledConditions>,
+) -> Vec<u32> {
+    let mut days = HashMap::new();
+    for view in views.iter().filter(|v| in_scope(scope, v, problem)) {
+        let Some(resource) = problem.value(view, resource) else {
+            continue;
+        };
+        let Some(span) = problem.span(view) else {
+            continue;
+        };
+        let location = ValueKey::of(problem.value(view, location)).text();
+        days.entry((ValueKey::of(Some(resource)), span.day.as_deref())).or_insert_with(Vec::new).push((span, location));
+    }
+
+    let mut violations = Vec::new();
+    for lessons in days.values_mut() {
+        lessons.sort_by_key(|(span, _)| span.start);
+        for pair in lessons.windows(2) {
+            let ((first, from), (second, to)) = (&pair[0], &pair[1]);
+            if problem.data.travel_minutes(from, to) > second.start.saturating_sub(first.end) {
+                violations.push(1);
+            }
         }
+    }
+
+    violations
+}
+
+fn evaluate_curriculum_conflict(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    curricula: &[CompiledCurriculum],
+) -> Vec<u32> {
+    let mut violations = Vec::new();
+    for CompiledCurriculum { curriculum, tasks } in curricula {
+        let spans: Vec<&TimeSpan> = views.iter()
+            .filter(|v| v.assignment.task_item_name == curriculum.task_item && tasks.contains(&v.assignment.task_id))
+            .filter_map(|v| problem.span(v))
+            .collect();
+        for (i, first) in spans.iter().enumerate() {
+            for second in &spans[i + 1..] {
+                if first.overlaps(second) {
+                    violations.push(curriculum.students.unwrap_or(1));
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+fn evaluate_availability(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    level: AvailabilityLevel,
+    items: &[usize],
+) -> Vec<u32> {
+    let mut violations = Vec::new();
+    for view in views {
+        let Some(span) = problem.span(view) else {
+            continue;
+        };
+        for &item in items {
+            let Some(windows) = problem.resource_availability(view, item) else {
+                continue;
+            };
+            if availability_breach(windows, span) == Some(level) {
+                violations.push(1);
+            }
+        }
+    }
+
+    violations
+}
+
+/// The worst way `span` breaches the windows, if it does.
+fn availability_breach(windows: &[AvailabilityWindow], span: &TimeSpan) -> Option<AvailabilityLevel> {
+    let of_kind = |kind| windows.iter().filter(move |w| w.kind == kind);
+
+    let has_available = of_kind(AvailabilityKind::Available).next().is_some();
+    let inside_available = of_kind(AvailabilityKind::Available).any(|w| window_contains(w, span));
+    let unavailable = of_kind(AvailabilityKind::Unavailable).any(|w| window_overlaps(w, span));
+    if unavailable || (has_available && !inside_available) {
+        return Some(AvailabilityLevel::Hard);
+    }
+    if of_kind(AvailabilityKind::PreferNot).any(|w| window_overlaps(w, span)) {
+        return Some(AvailabilityLevel::PreferNot);
+    }
+    None
+}
+
+/// Start and end of the window in minutes, if it applies on the span's day.
+fn window_bounds(window: &AvailabilityWindow, span: &TimeSpan) -> Option<(u32, u32)> {
+    let day = span.day.as_deref();
+    if let Some(weekday) = &window.weekday {
+        if day.and_then(time::weekday_of) != Some(time::weekday_index(weekday)?) {
+            return None;
+        }
+    }
+    if let Some(date) = &window.date {
+        if day.and_then(time::parse_date) != Some(time::parse_date(date)?) {
+            return None;
+        }
+    }
+    let start = window.start.as_deref().map_or(Some(0), time::parse_clock)?;
+    let end = window.end.as_deref().map_or(Some(24 * 60), time::parse_clock)?;
+    Some((start, end))
+}
+
+fn window_contains(window: &AvailabilityWindow, span: &TimeSpan) -> bool {
+    window_bounds(window, span).is_some_and(|(start, end)| start <= span.start && span.end <= end)
+}
+
+fn window_overlaps(window: &AvailabilityWindow, span: &TimeSpan) -> bool {
+    window_bounds(window, span).is_some_and(|(start, end)| span.start < end && start < span.end.max(span.start + 1))
+}
+
+fn evaluate_preferences(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    (row, column): (Option<usize>, Option<usize>),
+    default_score: u32,
+    scores: &HashMap<(ItemId, ItemId), u32>,
+) -> Vec<u32> {
+    let (Some(row), Some(column)) = (row, column) else {
+        return Vec::new();
     };
-    
-    if field_key == "id" {
-        return member_id.0.to_string();
-    }
-    
-    let item = match problem_data.item_categories.get(item_name) {
-        Some(i) => i,
-        None => return String::new(),
-    };
-    
-    let member = match item.members.iter().find(|m| m.id == member_id) {
-        Some(m) => m,
-        None => return String::new(),
-    };
-    
-    match member.fields.get(field_key) {
-        Some(Value::String(s)) => s.clone(),
-        Some(Value::Number(n)) => n.to_string(),
-        Some(Value::Date(d)) => d.clone(),
-        None => String::new(),
+
+    views.iter()
+        .filter_map(|v| Some((problem.member(v, row)?, problem.member(v, column)?)))
+        .map(|pair| scores.get(&pair).copied().unwrap_or(default_score))
+        .collect()
+}
+
+fn evaluate_compactness(
+    views: &[AssignmentView],
+    problem: &CompiledProblem,
+    resource: FieldRef,
+    scope: &Option<CompiledConditions>,
+    measure: &CompactnessMeasure,
+) -> Vec<u32> {
+    let mut lessons: HashMap<ValueKey, HashMap<Option<&str>, Vec<&TimeSpan>>> = HashMap::new();
+    for view in views.iter().filter(|v| in_scope(scope, v, problem)) {
+        let Some(resource) = problem.value(view, resource) else {
+            continue;
+        };
+        let Some(span) = problem.span(view) else {
+            continue;
+        };
+        lessons.entry(ValueKey::of(Some(resource))).or_default()
+            .entry(span.day.as_deref()).or_default()
+            .push(span);
+    }
+
+    let mut violations = Vec::new();
+    for days in lessons.values_mut() {
+        match measure {
+            CompactnessMeasure::TeachingDays { max_days } => {
+                violations.push((days.len() as u32).saturating_sub(*max_days));
+            }
+            CompactnessMeasure::IsolatedLessons => {
+                violations.extend(days.values().filter(|spans| spans.len() == 1).map(|_| 1));
+            }
+            CompactnessMeasure::IdleGaps => {
+                for (day, spans) in days.iter() {
+                    let first_start = spans.iter().map(|s| s.start).min().unwrap_or_default();
+                    let last_end = spans.iter().map(|s| s.end).max().unwrap_or_default();
+                    violations.push(problem.slot_spans()
+                        .filter(|slot| slot.day.as_deref() == *day && slot.start >= first_start && slot.end <= last_end)
+                        .filter(|slot| !spans.iter().any(|s| s.overlaps(slot)))
+                        .count() as u32);
+                }
+            }
+            CompactnessMeasure::ConsecutiveRun { max_consecutive, max_break_minutes } => {
+                for spans in days.values_mut() {
+                    spans.sort_by_key(|s| s.start);
+                    let mut run: u32 = 0;
+                    let mut run_end = 0;
+                    for span in spans.iter() {
+                        if run > 0 && span.start <= run_end + max_break_minutes {
+                            run += 1;
+                        } else {
+                            violations.push(run.saturating_sub(*max_consecutive));
+                            run = 1;
+                        }
+                        run_end = run_end.max(span.end);
+                    }
+                    violations.push(run.saturating_sub(*max_consecutive));
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    #[test]
+    fn saturated_penalties_do_not_wrap_the_total() {
+        let problem_data = timetable();
+        // Two courses in one room and one slot, and a lecture in a room that
+        // is too small
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 1)]);
+        let mut saturated = constraint(1, json!({ "GlobalAllDifferent": {
+            "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id",
+        } }));
+        saturated.weight = u32::MAX;
+        let other = constraint(2, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("Room", "capacity", "LessThan", &["30"])],
+            "logical_op": "And", "mode": "Forbid",
+        } }));
+        let constraints = vec![saturated, other];
+
+        let compiled = CompiledProblem::new(&problem_data, &constraints);
+        assert_eq!(compiled.evaluate(&schedule), u32::MAX);
+        let reports = compiled.report(&schedule);
+        assert_eq!(reports[0].penalty, u32::MAX);
+        assert_eq!(reports[1].penalty, 2);
+    }
+
+    #[test]
+    fn weight_and_penalty_function_scale_violations() {
+        let problem_data = timetable();
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 1), lesson(3, 1, 1, 1)]);
+        let mut quadratic = constraint(1, json!({ "GlobalAllDifferent": {
+            "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id",
+        } }));
+        quadratic.weight = 10;
+        quadratic.penalty = PenaltyFunction::Quadratic;
+
+        let reports = report_schedule(&schedule, &problem_data, &[quadratic]);
+        // One group with three assignments but one distinct room
+        assert_eq!(reports[0].violations, 2);
+        assert_eq!(reports[0].penalty, 40);
+    }
+
+    fn operand(multiplier: Option<f64>, offset: Option<i32>) -> FieldOperand {
+        FieldOperand { item_name: "TimeSlot".to_string(), field_key: "start".to_string(), multiplier, offset }
+    }
+
+    #[test]
+    fn field_operands_compare_times_by_clock_and_numbers_scaled() {
+        let date = ValueRef::Date;
+        let op = &ComparisonOperator::GreaterThan;
+        assert!(compare_with_operand(date("13:00"), op, date("9:00"), &operand(None, None)));
+        assert!(!compare_with_operand(date("9:00"), op, date("13:00"), &operand(None, None)));
+        assert!(compare_with_operand(date("10:15"), op, date("9:00"), &operand(None, Some(60))));
+        assert!(!compare_with_operand(date("10:00"), op, date("9:00"), &operand(None, Some(60))));
+        assert!(compare_with_operand(date("2025-10-07"), op, date("2025-10-06T23:00"), &operand(None, None)));
+        assert!(compare_with_operand(date("2026-01-01T00:10"), op, date("2025-12-31T23:50"), &operand(None, Some(15))));
+        // A time of day has no order against a date
+        assert!(!compare_with_operand(date("13:00"), op, date("2025-10-06"), &operand(None, None)));
+
+        let number = ValueRef::Number;
+        assert!(compare_with_operand(number(41), op, number(20), &operand(Some(2.0), None)));
+        assert!(!compare_with_operand(number(40), op, number(20), &operand(Some(2.0), None)));
+        assert!(compare_with_operand(number(40), op, number(20), &operand(Some(1.5), Some(9))));
+    }
+
+    #[test]
+    fn target_times_compare_by_clock() {
+        let problem_data = timetable();
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 2, 1, 3), lesson(4, 2, 2, 4)]);
+        let forbid = |operator: &str, targets: &[&str]| constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("TimeSlot", "start", operator, targets)],
+            "logical_op": "And", "mode": "Forbid",
+        } }));
+
+        let violations = |operator: &str, targets: &[&str]| report_schedule(&schedule, &problem_data, &[forbid(operator, targets)])[0].violations;
+        // Slots start at 08:00, 09:00, 11:00 and 08:00
+        assert_eq!(violations("Before", &["9:30"]), 3);
+        assert_eq!(violations("After", &["9:30"]), 1);
+        assert_eq!(violations("Equal", &["8:00"]), 2);
+        assert_eq!(violations("Overlap", &["8:30", "9:30"]), 1);
+        assert_eq!(violations("Before", &["2025-10-06T09:30"]), 0);
+    }
+
+    #[test]
+    fn conditions_compare_against_another_field_of_the_assignment() {
+        let problem_data = timetable();
+        // Course 1 has 40 students in room 1 (20 seats), course 2 has 15
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 2, 1, 3)]);
+        let too_small = |multiplier: Option<f64>| constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": [{
+                "item_name": "Room", "field_key": "capacity", "operator": "LessThan",
+                "target_field": { "item_name": "Course", "field_key": "students", "multiplier": multiplier },
+            }],
+            "logical_op": "And", "mode": "Forbid",
+        } }));
+
+        let reports = report_schedule(&schedule, &problem_data, &[too_small(None), too_small(Some(0.5))]);
+        assert_eq!(reports[0].violations, 1);
+        assert_eq!(reports[1].violations, 0);
+    }
+
+    fn balance(target: &str, measure: &str) -> Constraint {
+        constraint(1, json!({ "GlobalBalance": {
+            "target_item_field": target, "sum_item_field": null, "measure": measure, "scope_conditions": null,
+        } }))
+    }
+
+    #[test]
+    fn balance_measures_spread_of_counts_per_member() {
+        let problem_data = timetable();
+        // Room 1 holds three lessons, room 2 one and room 3 none
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 1, 1, 3), lesson(4, 2, 2, 4)]);
+        let constraints = [balance("Room:id", "Range"), balance("Room:id", "Variance"), balance("Room:id", "AbsoluteDeviation")];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.violations)
+            .collect();
+        // Totals 3, 1, 0 around a mean of 4/3
+        assert_eq!(violations, [3, 2, 3]);
+    }
+
+    #[test]
+    fn members_without_a_value_are_left_out_of_the_balance() {
+        let mut problem_data = timetable();
+        let rooms = problem_data.item_categories.get_mut("Room").unwrap();
+        rooms.members[2].fields.remove("building");
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 2, 2)]);
+        // One lesson in the North building and one in the South; room 3 has
+        // no building and adds no empty one
+        let reports = report_schedule(&schedule, &problem_data, &[balance("Room:building", "Range")]);
+        assert_eq!(reports[0].violations, 0);
+    }
+
+    fn pairwise(first: &[&str], second: &[&str], relation: serde_json::Value) -> Constraint {
+        constraint(1, json!({ "PairwiseRelation": {
+            "first_conditions": [condition("Course", "id", "In", first)],
+            "second_conditions": [condition("Course", "id", "In", second)],
+            "relation": relation,
+        } }))
+    }
+
+    #[test]
+    fn symmetric_pairs_are_counted_once() {
+        let problem_data = timetable();
+        // Courses 1, 2 and 3 all on Monday, course 4 on Tuesday
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 2, 2), lesson(3, 3, 1, 3), lesson(4, 1, 2, 4)]);
+        let constraints = [
+            pairwise(&["1", "2", "3", "4"], &["1", "2", "3", "4"], json!("DifferentDay")),
+            pairwise(&["1", "2"], &["2", "3"], json!("DifferentDay")),
+            pairwise(&["1", "2"], &["1", "2"], json!("Before")),
+            pairwise(&["1"], &["2"], json!({ "MinutesApart": 30 })),
+        ];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.violations)
+            .collect();
+        // The Monday pairs 1-2, 1-3 and 2-3 fail once each under either
+        // filter; Before checks both orders and only 2 before 1 fails; 1 ends
+        // at 09:00 just as 2 starts
+        assert_eq!(violations, [3, 3, 1, 1]);
+    }
+
+    #[test]
+    fn disabled_constraints_are_not_evaluated() {
+        let problem_data = timetable();
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 1)]);
+        let mut rooms_differ = constraint(1, json!({ "GlobalAllDifferent": {
+            "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id",
+        } }));
+        assert_eq!(evaluate_schedule(&schedule, &problem_data, std::slice::from_ref(&rooms_differ)), 1);
+
+        rooms_differ.enabled = false;
+        assert_eq!(evaluate_schedule(&schedule, &problem_data, std::slice::from_ref(&rooms_differ)), 0);
+        assert!(report_schedule(&schedule, &problem_data, &[rooms_differ]).is_empty());
+    }
+
+    #[test]
+    fn expressions_nest_and_or_not_and_implies() {
+        let problem_data = timetable();
+        let leaf = |item: &str, field: &str, operator: &str, target: &str| json!({ "Condition": condition(item, field, operator, &[target]) });
+        // Forbid Monday lessons from 09:00 on, and any lesson of lecturer 1
+        // that is not on Tuesday
+        let expression = json!({ "Or": [
+            { "And": [leaf("TimeSlot", "day", "Equal", "Monday"), { "Not": leaf("TimeSlot", "start", "LessThan", "09:00") }] },
+            { "Not": { "Implies": {
+                "antecedent": leaf("Lecturer", "id", "Equal", "1"),
+                "consequent": leaf("TimeSlot", "day", "Equal", "Tuesday"),
+            } } },
+        ] });
+        let forbid = constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": expression, "logical_op": "And", "mode": "Forbid",
+        } }));
+        let schedule = Schedule::new(vec![lesson(1, 1, 2, 1), lesson(2, 1, 2, 2), lesson(3, 1, 1, 1), lesson(4, 1, 1, 4)]);
+
+        // Course 2 starts at 09:00 on Monday; course 3 has lecturer 1 on Monday
+        let reports = report_schedule(&schedule, &problem_data, &[forbid]);
+        assert_eq!(reports[0].violations, 2);
+    }
+
+    #[test]
+    fn implications_only_bind_assignments_meeting_the_antecedent() {
+        let problem_data = timetable();
+        let lectures_in_large_rooms = constraint(1, json!({ "Implication": {
+            "antecedent": [condition("Course", "kind", "Equal", &["lecture"])],
+            "consequent": [condition("Room", "capacity", "GreaterThanOrEqual", &["40"])],
+        } }));
+        // Lectures 1 and 3 and exercise 2 in the small room 1, lecture 3 also in room 2
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 1, 1, 3), lesson(3, 2, 1, 4)]);
+
+        let reports = report_schedule(&schedule, &problem_data, &[lectures_in_large_rooms]);
+        assert_eq!(reports[0].violations, 2);
+    }
+
+    fn lessons_per(target: &str, min: Option<u32>, max: Option<u32>, include_unused: bool) -> Constraint {
+        constraint(1, json!({ "GlobalCardinality": {
+            "target_item_field": target, "min_count": min, "max_count": max, "scope_conditions": null,
+            "include_unused": include_unused,
+        } }))
+    }
+
+    #[test]
+    fn cardinality_bounds_counts_from_both_sides() {
+        let mut problem_data = timetable();
+        problem_data.item_categories.get_mut("Room").unwrap().members[2].fields.remove("building");
+        // Lecturer 1 teaches three lessons, lecturer 2 none; rooms 1 and 2 are used
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 1, 2), lesson(3, 2, 1, 3)]);
+        let constraints = [
+            lessons_per("Lecturer:id", Some(1), Some(2), false),
+            lessons_per("Lecturer:id", Some(1), Some(2), true),
+            lessons_per("Room:building", Some(1), None, true),
+            lessons_per("Course:id", None, Some(0), false),
+        ];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.violations)
+            .collect();
+        // Room 3 has no building, which adds no empty building short of a lesson
+        assert_eq!(violations, [1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn cardinality_counts_per_group_and_sums_a_field() {
+        let problem_data = timetable();
+        // Lecturer 1 teaches courses 1 and 2 on Monday and course 3 on Tuesday;
+        // every course lasts 90 minutes
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 1, 2), lesson(3, 3, 1, 4)]);
+        let per_day = |sum: Option<&str>, max: u32| constraint(1, json!({ "GlobalCardinality": {
+            "target_item_field": "Lecturer:id", "max_count": max, "scope_conditions": null,
+            "group_item_fields": ["TimeSlot:day"], "sum_item_field": sum,
+        } }));
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &[per_day(None, 1), per_day(Some("Course:duration"), 120)])
+            .iter()
+            .map(|report| report.violations)
+            .collect();
+        // Two lessons on Monday, 180 minutes against 120
+        assert_eq!(violations, [1, 60]);
+    }
+
+    #[test]
+    fn compactness_measures_each_lecturers_days() {
+        let problem_data = timetable();
+        // Lecturer 1 teaches Monday 08-09 and 11-12 and Tuesday 08-09,
+        // lecturer 2 Monday 09-10
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 1, 3), lesson(3, 1, 1, 4), lesson(4, 2, 2, 2)]);
+        let compactness = |measure: serde_json::Value| constraint(1, json!({ "Compactness": {
+            "resource_item_field": "Lecturer:id", "scope_conditions": null, "measure": measure,
+        } }));
+        let constraints = [
+            compactness(json!("IdleGaps")),
+            compactness(json!("IsolatedLessons")),
+            compactness(json!({ "TeachingDays": { "max_days": 1 } })),
+            compactness(json!({ "ConsecutiveRun": { "max_consecutive": 1 } })),
+            compactness(json!({ "ConsecutiveRun": { "max_consecutive": 1, "max_break_minutes": 120 } })),
+        ];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.violations)
+            .collect();
+        // The free 09-10 slot on lecturer 1's Monday; one lesson on lecturer
+        // 1's Tuesday and lecturer 2's Monday; lecturer 1's second day; the
+        // Monday lessons only form a run when two hours apart still count
+        assert_eq!(violations, [1, 2, 1, 0, 1]);
+    }
+
+    #[test]
+    fn spread_asks_for_distinct_days_within_each_group() {
+        let problem_data = timetable();
+        // Module A on Monday twice, module B on Monday and Tuesday
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 2, 1, 1), lesson(4, 2, 1, 4)]);
+        let spread = |min_distinct: Option<u32>, scope: serde_json::Value| constraint(1, json!({ "GlobalSpread": {
+            "group_item_field": "Course:module", "spread_item_field": "TimeSlot:day",
+            "min_distinct": min_distinct, "scope_conditions": scope,
+        } }));
+        let constraints = [
+            spread(None, json!(null)),
+            spread(Some(1), json!(null)),
+            spread(None, json!([condition("Course", "kind", "Equal", &["lecture"])])),
+        ];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.violations)
+            .collect();
+        assert_eq!(violations, [1, 0, 0]);
+    }
+
+    #[test]
+    fn curriculum_conflicts_weigh_by_students() {
+        let mut problem_data = timetable();
+        problem_data.curricula = vec![curriculum("Year 1", &[1, 2], Some(30)), curriculum("Year 2", &[3, 4], None)];
+        // Both Year 1 courses at Monday 08:00; Year 2 courses back to back
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 2, 1), lesson(3, 3, 1, 2), lesson(4, 3, 2, 3)]);
+        let constraints = [
+            constraint(1, json!({ "CurriculumConflict": {} })),
+            constraint(2, json!({ "CurriculumConflict": { "curricula": ["Year 2"] } })),
+        ];
+
+        let penalties: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.penalty)
+            .collect();
+        assert_eq!(penalties, [30, 0]);
+
+        let schedule = Schedule::new(vec![lesson(3, 3, 1, 2), lesson(4, 3, 2, 2)]);
+        assert_eq!(evaluate_schedule(&schedule, &problem_data, &constraints), 2);
+    }
+
+    #[test]
+    fn availability_separates_hard_breaches_from_dispreferred_times() {
+        let mut problem_data = timetable();
+        let lecturers = &mut problem_data.item_categories.get_mut("Lecturer").unwrap().members;
+        lecturers[0].availability = vec![window(AvailabilityKind::Available, Some("Monday"), Some("08:00"), Some("10:00"))];
+        lecturers[1].availability = vec![
+            window(AvailabilityKind::Unavailable, Some("Tuesday"), None, None),
+            window(AvailabilityKind::PreferNot, None, Some("09:30"), Some("12:00")),
+        ];
+        // Lecturer 1 on Monday 08-09 and 11-12, lecturer 2 on Monday 09-10
+        // and Tuesday 08-09
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 1, 3), lesson(3, 1, 2, 2), lesson(4, 1, 2, 4)]);
+        let constraints = [
+            constraint(1, json!({ "ResourceAvailability": { "level": "Hard" } })),
+            constraint(2, json!({ "ResourceAvailability": { "level": "PreferNot" } })),
+        ];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|report| report.violations)
+            .collect();
+        assert_eq!(violations, [2, 1]);
+    }
+
+    #[test]
+    fn preference_scores_fall_back_to_the_default() {
+        let problem_data = timetable();
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 1, 1, 3)]);
+
+        let reports = report_schedule(&schedule, &problem_data, &[preferences(1, 1, &[(1, 1, 5), (2, 2, 0)])]);
+        assert_eq!(reports[0].violations, 6);
+    }
+
+    #[test]
+    fn groups_count_each_resource_beyond_the_allowed_number() {
+        let problem_data = timetable();
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 1, 2, 2), lesson(3, 2, 1, 3), lesson(4, 2, 1, 4)]);
+        let consistency = |max_distinct: u32| constraint(1, json!({ "GlobalConsistency": {
+            "group_item_field": "Course:module", "resource_item_field": "Lecturer:id", "max_distinct": max_distinct,
+        } }));
+
+        assert_eq!(report_schedule(&schedule, &problem_data, &[consistency(1)])[0].violations, 1);
+        assert_eq!(report_schedule(&schedule, &problem_data, &[consistency(2)])[0].violations, 0);
+    }
+
+    #[test]
+    fn lecturers_need_time_to_walk_between_buildings() {
+        let mut problem_data = timetable();
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(3, 2, 1, 2), lesson(2, 1, 1, 3), lesson(4, 1, 2, 4)]);
+        let travel = [constraint(1, json!({ "TravelTime": {
+            "resource_item_field": "Lecturer:id", "location_item_field": "Room:building",
+        } }))];
+
+        assert_eq!(report_schedule(&schedule, &problem_data, &travel)[0].violations, 0);
+        problem_data.location_distances = vec![LocationDistance { from: "South".to_string(), to: "North".to_string(), minutes: 60 }];
+        assert_eq!(report_schedule(&schedule, &problem_data, &travel)[0].violations, 1);
+        problem_data.location_distances[0].minutes = 90;
+        assert_eq!(report_schedule(&schedule, &problem_data, &travel)[0].violations, 2);
+    }
+
+    #[test]
+    fn precedence_reads_times_from_the_chosen_item() {
+        let mut problem_data = timetable();
+        let course = problem_data.item_categories.get_mut("Course").unwrap();
+        for field in ["starts", "ends"] {
+            course.schema.definitions.insert(field.to_string(), FieldSchema {
+                field_name: field.to_string(),
+                field_type: FieldType::DateTime,
+                is_required: true,
+            });
+        }
+        for (member, (starts, ends)) in course.members.iter_mut().zip([
+            ("2026-10-12", "2026-10-16"),
+            ("2026-10-05", "2026-10-09"),
+            ("2026-10-12", "2026-10-16"),
+            ("2026-10-05", "2026-10-09"),
+        ]) {
+            member.fields.insert("starts".to_string(), clock(starts));
+            member.fields.insert("ends".to_string(), clock(ends));
+        }
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 2), lesson(2, 1, 2, 1), lesson(3, 2, 1, 1), lesson(4, 2, 2, 3)]);
+        let lectures_first = |temporal: serde_json::Value| {
+            let mut rule = json!({
+                "grouping_item_field": "Course:module",
+                "first_conditions": [condition("Course", "kind", "Equal", &["lecture"])],
+                "second_conditions": [condition("Course", "kind", "Equal", &["exercise"])],
+                "temporal_relation": "Before",
+            });
+            rule.as_object_mut().unwrap().extend(temporal.as_object().unwrap().clone());
+            constraint(1, json!({ "GlobalTemporalPrecedence": rule }))
+        };
+
+        let by_slot = lectures_first(json!({ "temporal_fields": ["start", "end"] }));
+        let by_course = lectures_first(json!({ "temporal_item": "Course", "temporal_fields": ["starts", "ends"] }));
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &[by_slot, by_course]).iter()
+            .map(|r| r.violations)
+            .collect();
+        assert_eq!(violations, [1, 2]);
+    }
+
+    #[test]
+    fn precedence_compares_unpadded_times_by_clock() {
+        let mut problem_data = timetable();
+        let slots = &mut problem_data.item_categories.get_mut("TimeSlot").unwrap().members;
+        slots[1].fields.insert("start".to_string(), clock("9:00"));
+        slots[1].fields.insert("end".to_string(), clock("9:59"));
+        slots[3].fields.insert("start".to_string(), clock("2026-10-06T08:00"));
+        slots[3].fields.insert("end".to_string(), clock("2026-10-06T09:00"));
+        let lectures_first = [constraint(1, json!({ "GlobalTemporalPrecedence": {
+            "grouping_item_field": "Course:module",
+            "first_conditions": [condition("Course", "kind", "Equal", &["lecture"])],
+            "second_conditions": [condition("Course", "kind", "Equal", &["exercise"])],
+            "temporal_relation": "Before",
+            "temporal_fields": ["start", "end"],
+        } }))];
+
+        // Module A has its lecture at 11:00 and its exercise at 9:00; module
+        // B mixes a time of day with a date, which cannot be ordered
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 3), lesson(2, 1, 2, 2), lesson(3, 2, 1, 1), lesson(4, 2, 2, 4)]);
+        assert_eq!(report_schedule(&schedule, &problem_data, &lectures_first)[0].violations, 2);
+        // Swapped round, module A keeps its order
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 2), lesson(2, 1, 2, 3), lesson(3, 2, 1, 1), lesson(4, 2, 2, 4)]);
+        assert_eq!(report_schedule(&schedule, &problem_data, &lectures_first)[0].violations, 1);
+    }
+
+    #[test]
+    fn tasks_without_a_resource_are_not_another_resource() {
+        let problem_data = timetable();
+        let mut unassigned = lesson(2, 1, 2, 1);
+        unassigned.resources.remove("Lecturer");
+        let mut also_unassigned = lesson(3, 2, 2, 1);
+        also_unassigned.resources.remove("Lecturer");
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 2), unassigned, also_unassigned]);
+        let constraints = [
+            constraint(1, json!({ "GlobalConsistency": {
+                "group_item_field": "Course:module", "resource_item_field": "Lecturer:id", "max_distinct": 1,
+            } })),
+            constraint(2, json!({ "GlobalAllDifferent": {
+                "unique_item_field": "Lecturer:id", "group_item_field": "TimeSlot:id",
+            } })),
+        ];
+
+        let violations: Vec<u32> = report_schedule(&schedule, &problem_data, &constraints).iter()
+            .map(|r| r.violations)
+            .collect();
+        assert_eq!(violations, [0, 0]);
     }
 }
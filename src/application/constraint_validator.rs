@@ -0,0 +1,852 @@
+use crate::domain::{time::{self, is_valid_datetime}, *};
+
+/// Checks a constraint against the current items and schemas.
+///
+/// Returns every problem found, each prefixed with where in the rule it sits,
+/// so a client can fix them all in one round trip.
+pub fn validate_constraint(constraint: &Constraint, problem_data: &ProblemData) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if constraint.name.trim().is_empty() {
+        errors.push("name: must not be empty".to_string());
+    }
+
+    match &constraint.rule {
+        ConstraintRule::MultiAssignmentCheck { conditions, .. } => {
+            validate_conditions("conditions", conditions, problem_data, &mut errors);
+        }
+        ConstraintRule::GlobalAllDifferent { unique_item_field, group_item_field } => {
+            validate_item_field("unique_item_field", unique_item_field, problem_data, &mut errors);
+            validate_item_field("group_item_field", group_item_field, problem_data, &mut errors);
+        }
+        ConstraintRule::GlobalCardinality {
+            target_item_field,
+            min_count,
+            max_count,
+            scope_conditions,
+            group_item_fields,
+            sum_item_field,
+            ..
+        } => {
+            validate_item_field("target_item_field", target_item_field, problem_data, &mut errors);
+            if let Some(conditions) = scope_conditions {
+                validate_conditions("scope_conditions", conditions, problem_data, &mut errors);
+            }
+            for (index, group_item_field) in group_item_fields.iter().enumerate() {
+                let location = format!("group_item_fields[{}]", index);
+                validate_item_field(&location, group_item_field, problem_data, &mut errors);
+            }
+            if let Some(sum_item_field) = sum_item_field {
+                validate_sum_field(sum_item_field, problem_data, &mut errors);
+            }
+            match (min_count, max_count) {
+                (None, None) => errors.push("min_count, max_count: at least one must be set".to_string()),
+                (Some(min), Some(max)) if min > max => {
+                    errors.push(format!("min_count: {} is greater than max_count {}", min, max));
+                }
+                _ => {}
+            }
+        }
+        ConstraintRule::GlobalTemporalPrecedence {
+            grouping_item_field,
+            first_conditions,
+            second_conditions,
+            temporal_relation,
+            temporal_fields,
+            temporal_item,
+        } => {
+            validate_item_field("grouping_item_field", grouping_item_field, problem_data, &mut errors);
+            validate_conditions("first_conditions", first_conditions, problem_data, &mut errors);
+            validate_conditions("second_conditions", second_conditions, problem_data, &mut errors);
+
+            if matches!(temporal_relation, ComparisonOperator::In | ComparisonOperator::NotIn) {
+                errors.push(format!("temporal_relation: {:?} does not compare two times", temporal_relation));
+            }
+            let temporal_item = temporal_item.as_deref().unwrap_or(&problem_data.time().item_name);
+            validate_temporal_fields("temporal_fields", temporal_item, temporal_fields, problem_data, &mut errors);
+        }
+        ConstraintRule::Implication { antecedent, consequent } => {
+            validate_conditions("antecedent", antecedent, problem_data, &mut errors);
+            validate_conditions("consequent", consequent, problem_data, &mut errors);
+        }
+        ConstraintRule::PairwiseRelation { first_conditions, second_conditions, relation } => {
+            validate_conditions("first_conditions", first_conditions, problem_data, &mut errors);
+            validate_conditions("second_conditions", second_conditions, problem_data, &mut errors);
+
+            match relation {
+                PairRelation::SameRoom | PairRelation::DifferentRoom => {
+                    let space = &problem_data.space().item_name;
+                    if !problem_data.item_categories.contains_key(space) {
+                        errors.push(format!("relation: {:?} needs an item named \"{}\"", relation, space));
+                    }
+                }
+                _ => validate_time_dimension(problem_data, &mut errors),
+            }
+        }
+        ConstraintRule::GlobalSpread { group_item_field, spread_item_field, min_distinct, scope_conditions } => {
+            validate_item_field("group_item_field", group_item_field, problem_data, &mut errors);
+            validate_item_field("spread_item_field", spread_item_field, problem_data, &mut errors);
+            if let Some(conditions) = scope_conditions {
+                validate_conditions("scope_conditions", conditions, problem_data, &mut errors);
+            }
+            if *min_distinct == Some(0) {
+                errors.push("min_distinct: must be at least 1".to_string());
+            }
+        }
+        ConstraintRule::GlobalConsistency { group_item_field, resource_item_field, max_distinct, scope_conditions } => {
+            validate_item_field("group_item_field", group_item_field, problem_data, &mut errors);
+            validate_item_field("resource_item_field", resource_item_field, problem_data, &mut errors);
+            if let Some(conditions) = scope_conditions {
+                validate_conditions("scope_conditions", conditions, problem_data, &mut errors);
+            }
+            if *max_distinct == 0 {
+                errors.push("max_distinct: must be at least 1".to_string());
+            }
+        }
+        ConstraintRule::TravelTime { resource_item_field, location_item_field, scope_conditions } => {
+            validate_item_field("resource_item_field", resource_item_field, problem_data, &mut errors);
+            validate_item_field("location_item_field", location_item_field, problem_data, &mut errors);
+            if let Some(conditions) = scope_conditions {
+                validate_conditions("scope_conditions", conditions, problem_data, &mut errors);
+            }
+            validate_time_dimension(problem_data, &mut errors);
+        }
+        ConstraintRule::GlobalBalance { target_item_field, sum_item_field, scope_conditions, .. } => {
+            validate_item_field("target_item_field", target_item_field, problem_data, &mut errors);
+            if let Some(sum_item_field) = sum_item_field {
+                validate_sum_field(sum_item_field, problem_data, &mut errors);
+            }
+            if let Some(conditions) = scope_conditions {
+                validate_conditions("scope_conditions", conditions, problem_data, &mut errors);
+            }
+        }
+        ConstraintRule::CurriculumConflict { curricula } => {
+            for name in curricula {
+                if !problem_data.curricula.iter().any(|c| c.name == *name) {
+                    errors.push(format!("curricula: curriculum \"{}\" does not exist", name));
+                }
+            }
+            validate_time_dimension(problem_data, &mut errors);
+        }
+        ConstraintRule::ResourceAvailability { item_names, .. } => {
+            for item_name in item_names {
+                if !problem_data.item_categories.contains_key(item_name) {
+                    errors.push(format!("item_names: item \"{}\" does not exist", item_name));
+                }
+            }
+            validate_time_dimension(problem_data, &mut errors);
+        }
+        ConstraintRule::PreferenceMatrix { row_item, column_item, scores, .. } => {
+            let rows = problem_data.item_categories.get(row_item);
+            let columns = problem_data.item_categories.get(column_item);
+            if rows.is_none() {
+                errors.push(format!("row_item: item \"{}\" does not exist", row_item));
+            }
+            if columns.is_none() {
+                errors.push(format!("column_item: item \"{}\" does not exist", column_item));
+            }
+            if row_item == column_item {
+                errors.push("column_item: must differ from row_item".to_string());
+            }
+
+            let mut seen = std::collections::HashSet::new();
+            for (index, score) in scores.iter().enumerate() {
+                let location = format!("scores[{}]", index);
+                if rows.is_some_and(|item| item.member(score.row_id).is_none()) {
+                    errors.push(format!("{}: item \"{}\" has no member {}", location, row_item, score.row_id.0));
+                }
+                if columns.is_some_and(|item| item.member(score.column_id).is_none()) {
+                    errors.push(format!("{}: item \"{}\" has no member {}", location, column_item, score.column_id.0));
+                }
+                if !seen.insert((score.row_id, score.column_id)) {
+                    errors.push(format!("{}: pair ({}, {}) is scored twice", location, score.row_id.0, score.column_id.0));
+                }
+            }
+        }
+        ConstraintRule::Compactness { resource_item_field, scope_conditions, .. } => {
+            validate_item_field("resource_item_field", resource_item_field, problem_data, &mut errors);
+            if let Some(conditions) = scope_conditions {
+                validate_conditions("scope_conditions", conditions, problem_data, &mut errors);
+            }
+            validate_time_dimension(problem_data, &mut errors);
+        }
+    }
+
+    if let PenaltyFunction::Capped { cap: 0 } = constraint.penalty {
+        errors.push("penalty: cap must be at least 1".to_string());
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Checks that a curriculum's task item and tasks exist.
+pub fn validate_curriculum(curriculum: &Curriculum, problem_data: &ProblemData) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if curriculum.name.trim().is_empty() {
+        errors.push("name: must not be empty".to_string());
+    }
+
+    match problem_data.item_categories.get(&curriculum.task_item) {
+        None => errors.push(format!("task_item: item \"{}\" does not exist", curriculum.task_item)),
+        Some(item) => {
+            for (index, task_id) in curriculum.tasks.iter().enumerate() {
+                if item.member(*task_id).is_none() {
+                    errors.push(format!("tasks[{}]: item \"{}\" has no member {}", index, item.name, task_id.0));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Checks a member's availability windows; only `E_Set` members may have any.
+pub fn validate_availability(item: &Item, windows: &[AvailabilityWindow]) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if !windows.is_empty() && item.item_set_type != SetType::E_Set {
+        errors.push(format!("availability: members of \"{}\" are tasks, not resources", item.name));
+    }
+
+    for (index, window) in windows.iter().enumerate() {
+        let location = format!("availability[{}]", index);
+        if let Some(weekday) = &window.weekday {
+            if time::weekday_index(weekday).is_none() {
+                errors.push(format!("{}: \"{}\" is not a weekday", location, weekday));
+            }
+        }
+        if let Some(date) = &window.date {
+            if time::parse_date(date).is_none() {
+                errors.push(format!("{}: \"{}\" is not a date", location, date));
+            }
+        }
+        let start = window.start.as_deref().map(|t| (t, time::parse_clock(t)));
+        let end = window.end.as_deref().map(|t| (t, time::parse_clock(t)));
+        for (value, parsed) in start.iter().chain(end.iter()) {
+            if parsed.is_none() {
+                errors.push(format!("{}: \"{}\" is not a time", location, value));
+            }
+        }
+        if let (Some((_, Some(start))), Some((_, Some(end)))) = (start, end) {
+            if start >= end {
+                errors.push(format!("{}: start must be before end", location));
+            }
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Checks that a pairing links a task item to a resource item and that the
+/// members and fields it names exist.
+pub fn validate_pairing(pairing: &ResourcePairing, problem_data: &ProblemData) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    let tasks = problem_data.item_categories.get(&pairing.task_item);
+    let resources = problem_data.item_categories.get(&pairing.resource_item);
+    match tasks {
+        None => errors.push(format!("task_item: item \"{}\" does not exist", pairing.task_item)),
+        Some(item) if item.item_set_type != SetType::B_Set => {
+            errors.push(format!("task_item: \"{}\" is not a task item", item.name));
+        }
+        Some(_) => {}
+    }
+    match resources {
+        None => errors.push(format!("resource_item: item \"{}\" does not exist", pairing.resource_item)),
+        Some(item) if item.item_set_type != SetType::E_Set => {
+            errors.push(format!("resource_item: \"{}\" is not a resource item", item.name));
+        }
+        Some(_) => {}
+    }
+
+    match &pairing.rule {
+        PairingRule::Table { allowed } => {
+            let mut seen = std::collections::HashSet::new();
+            for (index, row) in allowed.iter().enumerate() {
+                let location = format!("rule.allowed[{}]", index);
+                if !seen.insert(row.task_id) {
+                    errors.push(format!("{}: task {} is listed twice", location, row.task_id.0));
+                }
+                if tasks.is_some_and(|item| item.member(row.task_id).is_none()) {
+                    errors.push(format!("{}: item \"{}\" has no member {}", location, pairing.task_item, row.task_id.0));
+                }
+                for resource_id in &row.resource_ids {
+                    if resources.is_some_and(|item| item.member(*resource_id).is_none()) {
+                        errors.push(format!("{}: item \"{}\" has no member {}", location, pairing.resource_item, resource_id.0));
+                    }
+                }
+            }
+        }
+        PairingRule::FieldMatch { task_field, resource_field } => {
+            let task_type = tasks.and_then(|_| {
+                lookup_field_type("rule.task_field", &pairing.task_item, task_field, problem_data, &mut errors)
+            });
+            let resource_type = resources.and_then(|_| {
+                lookup_field_type("rule.resource_field", &pairing.resource_item, resource_field, problem_data, &mut errors)
+            });
+            if let (Some(task_type), Some(resource_type)) = (task_type, resource_type) {
+                if task_type != resource_type {
+                    errors.push(format!(
+                        "rule: cannot match {:?} field \"{}\" with {:?} field \"{}\"",
+                        task_type, task_field, resource_type, resource_field
+                    ));
+                }
+            }
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Checks a distance list for self-distances and pairs given twice, in
+/// either direction.
+pub fn validate_distances(distances: &[LocationDistance]) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for (index, distance) in distances.iter().enumerate() {
+        let location = format!("distances[{}]", index);
+        if distance.from.trim().is_empty() || distance.to.trim().is_empty() {
+            errors.push(format!("{}: from and to must not be empty", location));
+        }
+        if distance.from == distance.to {
+            errors.push(format!("{}: from and to are both \"{}\"", location, distance.from));
+        }
+        let pair = if distance.from <= distance.to {
+            (&distance.from, &distance.to)
+        } else {
+            (&distance.to, &distance.from)
+        };
+        if !seen.insert(pair) {
+            errors.push(format!("{}: \"{}\" to \"{}\" is given twice", location, distance.from, distance.to));
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Checks that the time and space dimensions are resource items and that
+/// the time dimension's fields hold times.
+pub fn validate_dimensions(
+    time: Option<&TimeDimension>,
+    space: Option<&SpaceDimension>,
+    problem_data: &ProblemData,
+) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    if let Some(time) = time {
+        match problem_data.item_categories.get(&time.item_name) {
+            None => errors.push(format!("time_dimension: item \"{}\" does not exist", time.item_name)),
+            Some(item) if item.item_set_type != SetType::E_Set => {
+                errors.push(format!("time_dimension: \"{}\" is not a resource item", item.name));
+            }
+            Some(item) => {
+                // Without a day field the day is taken from the start date
+                let day_type = item.schema.definitions.get(&time.day_field).map(|d| &d.field_type);
+                if day_type == Some(&FieldType::Integer) {
+                    errors.push(format!(
+                        "time_dimension.day_field: \"{}:{}\" is Integer, not Text or DateTime",
+                        item.name, time.day_field
+                    ));
+                }
+                validate_temporal_fields(
+                    "time_dimension",
+                    &time.item_name,
+                    &[time.start_field.clone(), time.end_field.clone()],
+                    problem_data,
+                    &mut errors,
+                );
+            }
+        }
+    }
+
+    if let Some(space) = space {
+        match problem_data.item_categories.get(&space.item_name) {
+            None => errors.push(format!("space_dimension: item \"{}\" does not exist", space.item_name)),
+            Some(item) if item.item_set_type != SetType::E_Set => {
+                errors.push(format!("space_dimension: \"{}\" is not a resource item", item.name));
+            }
+            Some(_) => {}
+        }
+        if time.is_some_and(|t| t.item_name == space.item_name) {
+            errors.push("space_dimension: must differ from time_dimension".to_string());
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+fn validate_conditions(
+    location: &str,
+    conditions: &ConditionSet,
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) {
+    match conditions {
+        ConditionSet::List(list) => {
+            for (index, condition) in list.iter().enumerate() {
+                validate_condition(&format!("{}[{}]", location, index), condition, problem_data, errors);
+            }
+        }
+        ConditionSet::Expr(expr) => validate_expr(location, expr, problem_data, errors),
+    }
+}
+
+fn validate_expr(
+    location: &str,
+    expr: &ConditionExpr,
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) {
+    match expr {
+        ConditionExpr::Condition(condition) => validate_condition(location, condition, problem_data, errors),
+        ConditionExpr::And(exprs) | ConditionExpr::Or(exprs) => {
+            let operator = if matches!(expr, ConditionExpr::And(_)) { "And" } else { "Or" };
+            if exprs.is_empty() {
+                errors.push(format!("{}.{}: must contain at least one expression", location, operator));
+            }
+            for (index, expr) in exprs.iter().enumerate() {
+                validate_expr(&format!("{}.{}[{}]", location, operator, index), expr, problem_data, errors);
+            }
+        }
+        ConditionExpr::Not(expr) => validate_expr(&format!("{}.Not", location), expr, problem_data, errors),
+        ConditionExpr::Implies { antecedent, consequent } => {
+            validate_expr(&format!("{}.Implies.antecedent", location), antecedent, problem_data, errors);
+            validate_expr(&format!("{}.Implies.consequent", location), consequent, problem_data, errors);
+        }
+    }
+}
+
+fn validate_condition(
+    location: &str,
+    condition: &Condition,
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) {
+    let Some(field_type) = lookup_field_type(location, &condition.item_name, &condition.field_key, problem_data, errors) else {
+        return;
+    };
+
+    if !operator_applies_to(&condition.operator, &field_type) {
+        errors.push(format!(
+            "{}: operator {:?} cannot be used on {:?} field \"{}:{}\"",
+            location, condition.operator, field_type, condition.item_name, condition.field_key
+        ));
+    }
+
+    if let Some(operand) = &condition.target_field {
+        validate_operand(location, condition, &field_type, operand, problem_data, errors);
+        return;
+    }
+
+    if condition.target_values.is_empty() {
+        errors.push(format!("{}: target_values must not be empty", location));
+    }
+    for target in &condition.target_values {
+        if !parses_as(target, &field_type) {
+            errors.push(format!(
+                "{}: target value \"{}\" is not a valid {:?} for \"{}:{}\"",
+                location, target, field_type, condition.item_name, condition.field_key
+            ));
+        }
+    }
+}
+
+fn validate_operand(
+    location: &str,
+    condition: &Condition,
+    field_type: &FieldType,
+    operand: &FieldOperand,
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) {
+    let location = format!("{}.target_field", location);
+
+    if !condition.target_values.is_empty() {
+        errors.push(format!("{}: target_values must be empty when target_field is set", location));
+    }
+    if matches!(condition.operator, ComparisonOperator::Overlap | ComparisonOperator::NoOverlap) {
+        errors.push(format!("{}: operator {:?} needs target_values, not a field", location, condition.operator));
+    }
+
+    let Some(operand_type) = lookup_field_type(&location, &operand.item_name, &operand.field_key, problem_data, errors) else {
+        return;
+    };
+    if operand_type != *field_type {
+        errors.push(format!(
+            "{}: cannot compare {:?} field \"{}:{}\" with {:?} field \"{}:{}\"",
+            location, field_type, condition.item_name, condition.field_key,
+            operand_type, operand.item_name, operand.field_key
+        ));
+    }
+    if operand.multiplier.is_some() && operand_type != FieldType::Integer {
+        errors.push(format!("{}: multiplier only applies to Integer fields", location));
+    }
+    if operand.offset.is_some() && operand_type == FieldType::Text {
+        errors.push(format!("{}: offset only applies to Integer and DateTime fields", location));
+    }
+}
+
+fn validate_item_field(
+    location: &str,
+    item_field: &str,
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) -> Option<FieldType> {
+    let Some((item_name, field_key)) = item_field.split_once(':') else {
+        errors.push(format!("{}: expected \"Item:field\", got \"{}\"", location, item_field));
+        return None;
+    };
+    lookup_field_type(location, item_name, field_key, problem_data, errors)
+}
+
+fn validate_sum_field(sum_item_field: &str, problem_data: &ProblemData, errors: &mut Vec<String>) {
+    match validate_item_field("sum_item_field", sum_item_field, problem_data, errors) {
+        Some(FieldType::Integer) | None => {}
+        Some(field_type) => errors.push(format!(
+            "sum_item_field: \"{}\" is {:?}, not Integer",
+            sum_item_field, field_type
+        )),
+    }
+}
+
+/// Checks that rules reading the time of an assignment find the time
+/// dimension's start and end fields; errors name the time dimension, not the
+/// rule.
+fn validate_time_dimension(problem_data: &ProblemData, errors: &mut Vec<String>) {
+    let time = problem_data.time();
+    validate_temporal_fields(
+        "time_dimension",
+        &time.item_name,
+        &[time.start_field.clone(), time.end_field.clone()],
+        problem_data,
+        errors,
+    );
+}
+
+fn validate_temporal_fields(
+    location: &str,
+    temporal_item: &str,
+    temporal_fields: &[String],
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) {
+    if temporal_fields.is_empty() || temporal_fields.len() > 2 {
+        errors.push(format!("{}: expected a single field or a start and end field", location));
+    }
+    if !problem_data.item_categories.contains_key(temporal_item) {
+        errors.push(format!("{}: item \"{}\" does not exist", location, temporal_item));
+        return;
+    }
+    for field_key in temporal_fields {
+        match lookup_field_type(location, temporal_item, field_key, problem_data, errors) {
+            Some(FieldType::DateTime) | None => {}
+            Some(field_type) => errors.push(format!(
+                "{}: \"{}:{}\" is {:?}, not DateTime",
+                location, temporal_item, field_key, field_type
+            )),
+        }
+    }
+}
+
+fn lookup_field_type(
+    location: &str,
+    item_name: &str,
+    field_key: &str,
+    problem_data: &ProblemData,
+    errors: &mut Vec<String>,
+) -> Option<FieldType> {
+    let Some(item) = problem_data.item_categories.get(item_name) else {
+        errors.push(format!("{}: item \"{}\" does not exist", location, item_name));
+        return None;
+    };
+    if field_key == "id" {
+        return Some(FieldType::Integer);
+    }
+    match item.schema.definitions.get(field_key) {
+        Some(definition) => Some(definition.field_type.clone()),
+        None => {
+            errors.push(format!("{}: item \"{}\" has no field \"{}\"", location, item_name, field_key));
+            None
+        }
+    }
+}
+
+fn operator_applies_to(operator: &ComparisonOperator, field_type: &FieldType) -> bool {
+    let temporal = matches!(
+        operator,
+        ComparisonOperator::Before | ComparisonOperator::After | ComparisonOperator::Overlap | ComparisonOperator::NoOverlap
+    );
+    !temporal || *field_type == FieldType::DateTime
+}
+
+fn parses_as(target: &str, field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Text => true,
+        FieldType::Integer => target.trim().parse::<i32>().is_ok(),
+        FieldType::DateTime => is_valid_datetime(target),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    fn forbid(condition: serde_json::Value) -> Constraint {
+        constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition], "logical_op": "And", "mode": "Forbid",
+        } }))
+    }
+
+    #[test]
+    fn conditions_are_checked_against_the_schema() {
+        let problem_data = timetable();
+        assert!(validate_constraint(&forbid(condition("Room", "capacity", "LessThan", &["30"])), &problem_data).is_ok());
+        assert!(validate_constraint(&forbid(condition("TimeSlot", "start", "Before", &["9:30"])), &problem_data).is_ok());
+
+        let errors = |condition: serde_json::Value| validate_constraint(&forbid(condition), &problem_data).unwrap_err();
+        assert_eq!(errors(condition("Hall", "capacity", "Equal", &["1"])), [
+            "conditions[0]: item \"Hall\" does not exist",
+        ]);
+        assert_eq!(errors(condition("Room", "seats", "Equal", &["1"])), [
+            "conditions[0]: item \"Room\" has no field \"seats\"",
+        ]);
+        assert_eq!(errors(condition("Room", "capacity", "Before", &["thirty"])), [
+            "conditions[0]: operator Before cannot be used on Integer field \"Room:capacity\"",
+            "conditions[0]: target value \"thirty\" is not a valid Integer for \"Room:capacity\"",
+        ]);
+        assert_eq!(errors(condition("TimeSlot", "start", "After", &["25:00"])), [
+            "conditions[0]: target value \"25:00\" is not a valid DateTime for \"TimeSlot:start\"",
+        ]);
+        assert_eq!(errors(condition("Room", "id", "In", &[])), ["conditions[0]: target_values must not be empty"]);
+    }
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let problem_data = timetable();
+        let mut all_different = constraint(1, json!({ "GlobalAllDifferent": {
+            "unique_item_field": "Room", "group_item_field": "Slot:id",
+        } }));
+        all_different.name = " ".to_string();
+
+        assert_eq!(validate_constraint(&all_different, &problem_data).unwrap_err(), [
+            "name: must not be empty",
+            "unique_item_field: expected \"Item:field\", got \"Room\"",
+            "group_item_field: item \"Slot\" does not exist",
+        ]);
+    }
+
+    #[test]
+    fn field_operands_must_fit_the_compared_field() {
+        let problem_data = timetable();
+        // `field` compared with TimeSlot:start scaled and shifted as given
+        let operand = |field: &str, operator: &str, multiplier: Option<f64>, offset: Option<i32>| forbid(json!({
+            "item_name": "TimeSlot", "field_key": field, "operator": operator,
+            "target_field": { "item_name": "TimeSlot", "field_key": "start", "multiplier": multiplier, "offset": offset },
+        }));
+
+        assert!(validate_constraint(&operand("end", "GreaterThan", None, Some(30)), &problem_data).is_ok());
+        assert_eq!(
+            validate_constraint(&operand("end", "GreaterThan", Some(2.0), None), &problem_data),
+            Err(vec!["conditions[0].target_field: multiplier only applies to Integer fields".to_string()]),
+        );
+        assert_eq!(
+            validate_constraint(&operand("end", "Overlap", None, None), &problem_data),
+            Err(vec!["conditions[0].target_field: operator Overlap needs target_values, not a field".to_string()]),
+        );
+        assert_eq!(
+            validate_constraint(&operand("day", "Equal", None, None), &problem_data),
+            Err(vec![
+                "conditions[0].target_field: cannot compare Text field \"TimeSlot:day\" with DateTime field \"TimeSlot:start\"".to_string(),
+            ]),
+        );
+    }
+
+    #[test]
+    fn expressions_are_checked_at_every_level() {
+        let problem_data = timetable();
+        let expression = json!({ "And": [
+            { "Or": [] },
+            { "Not": { "Condition": condition("Room", "seats", "Equal", &["1"]) } },
+            { "Implies": {
+                "antecedent": { "Condition": condition("Room", "id", "Equal", &["1"]) },
+                "consequent": { "Condition": condition("Lecturer", "id", "Equal", &["one"]) },
+            } },
+        ] });
+        let forbid = constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": expression, "logical_op": "And", "mode": "Forbid",
+        } }));
+
+        assert_eq!(validate_constraint(&forbid, &problem_data).unwrap_err(), [
+            "conditions.And[0].Or: must contain at least one expression",
+            "conditions.And[1].Not: item \"Room\" has no field \"seats\"",
+            "conditions.And[2].Implies.consequent: target value \"one\" is not a valid Integer for \"Lecturer:id\"",
+        ]);
+    }
+
+    #[test]
+    fn cardinality_needs_a_consistent_bound() {
+        let problem_data = timetable();
+        let cardinality = |min: Option<u32>, max: Option<u32>| constraint(1, json!({ "GlobalCardinality": {
+            "target_item_field": "Lecturer:id", "min_count": min, "max_count": max, "scope_conditions": null,
+        } }));
+
+        assert!(validate_constraint(&cardinality(Some(1), Some(1)), &problem_data).is_ok());
+        assert_eq!(validate_constraint(&cardinality(Some(3), Some(1)), &problem_data).unwrap_err(), [
+            "min_count: 3 is greater than max_count 1",
+        ]);
+        assert_eq!(validate_constraint(&cardinality(None, None), &problem_data).unwrap_err(), [
+            "min_count, max_count: at least one must be set",
+        ]);
+    }
+
+    #[test]
+    fn cardinality_sums_need_an_integer_field() {
+        let problem_data = timetable();
+        let summed = |sum: &str| constraint(1, json!({ "GlobalCardinality": {
+            "target_item_field": "Lecturer:id", "max_count": 120, "scope_conditions": null,
+            "group_item_fields": ["TimeSlot:day", "TimeSlot:week"], "sum_item_field": sum,
+        } }));
+
+        assert_eq!(validate_constraint(&summed("Course:duration"), &problem_data).unwrap_err(), [
+            "group_item_fields[1]: item \"TimeSlot\" has no field \"week\"",
+        ]);
+        assert_eq!(validate_constraint(&summed("Course:name"), &problem_data).unwrap_err(), [
+            "group_item_fields[1]: item \"TimeSlot\" has no field \"week\"",
+            "sum_item_field: \"Course:name\" is Text, not Integer",
+        ]);
+    }
+
+    #[test]
+    fn curricula_list_existing_tasks() {
+        let problem_data = timetable();
+        assert!(validate_curriculum(&curriculum("Year 1", &[1, 2], Some(30)), &problem_data).is_ok());
+
+        let mut unknown = curriculum(" ", &[1, 9], None);
+        assert_eq!(validate_curriculum(&unknown, &problem_data).unwrap_err(), [
+            "name: must not be empty",
+            "tasks[1]: item \"Course\" has no member 9",
+        ]);
+        unknown.task_item = "Module".to_string();
+        assert_eq!(validate_curriculum(&unknown, &problem_data).unwrap_err()[1], "task_item: item \"Module\" does not exist");
+    }
+
+    #[test]
+    fn availability_windows_belong_to_resources_and_parse() {
+        let problem_data = timetable();
+        let lecturers = &problem_data.item_categories["Lecturer"];
+        let courses = &problem_data.item_categories["Course"];
+        let monday = window(AvailabilityKind::Available, Some("Monday"), Some("08:00"), Some("12:00"));
+        assert!(validate_availability(lecturers, std::slice::from_ref(&monday)).is_ok());
+
+        let windows = [
+            window(AvailabilityKind::Unavailable, Some("Someday"), Some("12:00"), Some("08:00")),
+            window(AvailabilityKind::PreferNot, None, Some("noon"), None),
+        ];
+        assert_eq!(validate_availability(lecturers, &windows).unwrap_err(), [
+            "availability[0]: \"Someday\" is not a weekday",
+            "availability[0]: start must be before end",
+            "availability[1]: \"noon\" is not a time",
+        ]);
+        assert_eq!(validate_availability(courses, &[monday]).unwrap_err(), [
+            "availability: members of \"Course\" are tasks, not resources",
+        ]);
+    }
+
+    #[test]
+    fn pairings_need_a_task_item_a_resource_item_and_existing_members() {
+        let problem_data = timetable();
+        assert!(validate_pairing(&lecturer_table(&[(1, &[2]), (2, &[1, 2])]), &problem_data).is_ok());
+
+        assert_eq!(validate_pairing(&lecturer_table(&[(1, &[2]), (1, &[3]), (9, &[])]), &problem_data).unwrap_err(), [
+            "rule.allowed[1]: task 1 is listed twice",
+            "rule.allowed[1]: item \"Lecturer\" has no member 3",
+            "rule.allowed[2]: item \"Course\" has no member 9",
+        ]);
+
+        let backwards = ResourcePairing {
+            task_item: "Room".to_string(),
+            resource_item: "Course".to_string(),
+            rule: PairingRule::FieldMatch { task_field: "capacity".to_string(), resource_field: "name".to_string() },
+        };
+        assert_eq!(validate_pairing(&backwards, &problem_data).unwrap_err(), [
+            "task_item: \"Room\" is not a task item",
+            "resource_item: \"Course\" is not a resource item",
+            "rule: cannot match Integer field \"capacity\" with Text field \"name\"",
+        ]);
+    }
+
+    #[test]
+    fn distances_are_given_once_between_two_places() {
+        let distance = |from: &str, to: &str| LocationDistance { from: from.to_string(), to: to.to_string(), minutes: 10 };
+        assert!(validate_distances(&[distance("North", "South"), distance("North", "East")]).is_ok());
+        assert_eq!(validate_distances(&[distance("North", "South"), distance("South", "North"), distance("", "East"), distance("East", "East")]).unwrap_err(), [
+            "distances[1]: \"South\" to \"North\" is given twice",
+            "distances[2]: from and to must not be empty",
+            "distances[3]: from and to are both \"East\"",
+        ]);
+    }
+
+    #[test]
+    fn temporal_fields_must_hold_times_on_the_temporal_item() {
+        let problem_data = timetable();
+        let precedence = |temporal_item: Option<&str>, fields: &[&str]| constraint(1, json!({ "GlobalTemporalPrecedence": {
+            "grouping_item_field": "Course:module",
+            "first_conditions": [], "second_conditions": [],
+            "temporal_relation": "Before", "temporal_fields": fields, "temporal_item": temporal_item,
+        } }));
+
+        assert!(validate_constraint(&precedence(None, &["start", "end"]), &problem_data).is_ok());
+        assert_eq!(validate_constraint(&precedence(Some("Room"), &["start"]), &problem_data).unwrap_err(), [
+            "temporal_fields: item \"Room\" has no field \"start\"",
+        ]);
+        assert_eq!(validate_constraint(&precedence(Some("Course"), &["name", "module", "kind"]), &problem_data).unwrap_err(), [
+            "temporal_fields: expected a single field or a start and end field",
+            "temporal_fields: \"Course:name\" is Text, not DateTime",
+            "temporal_fields: \"Course:module\" is Text, not DateTime",
+            "temporal_fields: \"Course:kind\" is Text, not DateTime",
+        ]);
+    }
+
+    #[test]
+    fn dimensions_are_resource_items_and_time_has_times() {
+        let problem_data = timetable();
+        let time = |item_name: &str| TimeDimension {
+            item_name: item_name.to_string(),
+            day_field: "day".to_string(),
+            start_field: "start".to_string(),
+            end_field: "end".to_string(),
+        };
+        let space = |item_name: &str| SpaceDimension { item_name: item_name.to_string() };
+
+        assert!(validate_dimensions(Some(&time("TimeSlot")), Some(&space("Room")), &problem_data).is_ok());
+        assert_eq!(validate_dimensions(Some(&time("Course")), Some(&space("Hall")), &problem_data).unwrap_err(), [
+            "time_dimension: \"Course\" is not a resource item",
+            "space_dimension: item \"Hall\" does not exist",
+        ]);
+        assert_eq!(validate_dimensions(Some(&time("Room")), Some(&space("Room")), &problem_data).unwrap_err(), [
+            "time_dimension: item \"Room\" has no field \"start\"",
+            "time_dimension: item \"Room\" has no field \"end\"",
+            "space_dimension: must differ from time_dimension",
+        ]);
+    }
+
+    #[test]
+    fn rules_reading_times_blame_the_time_dimension() {
+        let mut problem_data = timetable();
+        problem_data.item_categories.remove("TimeSlot");
+        let travel = constraint(1, json!({ "TravelTime": {
+            "resource_item_field": "Lecturer:id", "location_item_field": "Room:building",
+        } }));
+        let availability = constraint(2, json!({ "ResourceAvailability": { "level": "Hard" } }));
+
+        for constraint in [travel, availability] {
+            assert_eq!(validate_constraint(&constraint, &problem_data).unwrap_err(), [
+                "time_dimension: item \"TimeSlot\" does not exist",
+            ]);
+        }
+    }
+}
@@ -0,0 +1,728 @@
+use crate::domain::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Something that refers to an item or member and breaks when it goes away.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Dependent {
+    Constraint { id: ConstraintId, name: String },
+    ScheduleAssignment { task_item_name: String, task_id: ItemId },
+    Curriculum { name: String },
+    /// One task's place in a curriculum; removing it keeps the curriculum.
+    CurriculumTask { curriculum: String, task_id: ItemId },
+    /// A member's row or column in a preference matrix; removing it keeps
+    /// the constraint.
+    PreferenceScores { constraint_id: ConstraintId, item_name: String, member_id: ItemId },
+    Pairing { task_item: String, resource_item: String },
+    /// A member's entries in a pairing table; removing them keeps the pairing.
+    PairingMember { task_item: String, resource_item: String, item_name: String, member_id: ItemId },
+    /// The designation of an item as the time dimension; removing it falls
+    /// back to the default.
+    TimeDimension { item_name: String },
+    SpaceDimension { item_name: String },
+}
+
+impl fmt::Display for Dependent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dependent::Constraint { id, name } => write!(f, "constraint {} \"{}\"", id.0, name),
+            Dependent::ScheduleAssignment { task_item_name, task_id } => {
+                write!(f, "schedule assignment for {} {}", task_item_name, task_id.0)
+            }
+            Dependent::Curriculum { name } => write!(f, "curriculum \"{}\"", name),
+            Dependent::CurriculumTask { curriculum, task_id } => {
+                write!(f, "task {} in curriculum \"{}\"", task_id.0, curriculum)
+            }
+            Dependent::PreferenceScores { constraint_id, item_name, member_id } => {
+                write!(f, "preference scores for {} {} in constraint {}", item_name, member_id.0, constraint_id.0)
+            }
+            Dependent::Pairing { task_item, resource_item } => write!(f, "pairing {} -> {}", task_item, resource_item),
+            Dependent::PairingMember { task_item, resource_item, item_name, member_id } => {
+                write!(f, "{} {} in pairing {} -> {}", item_name, member_id.0, task_item, resource_item)
+            }
+            Dependent::TimeDimension { item_name } => write!(f, "time dimension \"{}\"", item_name),
+            Dependent::SpaceDimension { item_name } => write!(f, "space dimension \"{}\"", item_name),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BrokenReference {
+    pub dependent: Dependent,
+    pub reference: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    /// Refuse the delete while anything still refers to the target.
+    #[default]
+    Restrict,
+    /// Delete the target together with everything that refers to it.
+    Cascade,
+}
+
+pub fn item_dependents(
+    problem_data: &ProblemData,
+    item_name: &str,
+    constraints: &[Constraint],
+    schedule: Option<&Schedule>,
+) -> Vec<Dependent> {
+    let mut dependents: Vec<Dependent> = constraints.iter()
+        .filter(|c| constraint_mentions_item(c, item_name))
+        .map(|c| Dependent::Constraint { id: c.id, name: c.name.clone() })
+        .collect();
+
+    dependents.extend(problem_data.curricula.iter()
+        .filter(|c| c.task_item == item_name)
+        .map(|c| Dependent::Curriculum { name: c.name.clone() }));
+
+    dependents.extend(problem_data.pairings.iter()
+        .filter(|p| p.task_item == item_name || p.resource_item == item_name)
+        .map(pairing_dependent));
+
+    if problem_data.time_dimension.as_ref().is_some_and(|t| t.item_name == item_name) {
+        dependents.push(Dependent::TimeDimension { item_name: item_name.to_string() });
+    }
+    if problem_data.space_dimension.as_ref().is_some_and(|s| s.item_name == item_name) {
+        dependents.push(Dependent::SpaceDimension { item_name: item_name.to_string() });
+    }
+
+    if let Some(schedule) = schedule {
+        dependents.extend(schedule.assignments.iter()
+            .filter(|a| a.task_item_name == item_name || a.resources.contains_key(item_name))
+            .map(assignment_dependent));
+    }
+
+    dependents
+}
+
+pub fn member_dependents(
+    problem_data: &ProblemData,
+    item_name: &str,
+    member_id: ItemId,
+    constraints: &[Constraint],
+    schedule: Option<&Schedule>,
+) -> Vec<Dependent> {
+    let mut dependents: Vec<Dependent> = constraints.iter()
+        .filter(|c| constraint_mentions_member(c, item_name, member_id))
+        .map(|c| Dependent::Constraint { id: c.id, name: c.name.clone() })
+        .collect();
+
+    dependents.extend(problem_data.curricula.iter()
+        .filter(|c| c.includes(item_name, member_id))
+        .map(|c| Dependent::CurriculumTask { curriculum: c.name.clone(), task_id: member_id }));
+
+    dependents.extend(constraints.iter()
+        .filter(|c| has_preference_scores(c, item_name, member_id))
+        .map(|c| Dependent::PreferenceScores {
+            constraint_id: c.id,
+            item_name: item_name.to_string(),
+            member_id,
+        }));
+
+    dependents.extend(problem_data.pairings.iter()
+        .filter(|p| table_mentions_member(p, item_name, member_id))
+        .map(|p| Dependent::PairingMember {
+            task_item: p.task_item.clone(),
+            resource_item: p.resource_item.clone(),
+            item_name: item_name.to_string(),
+            member_id,
+        }));
+
+    if let Some(schedule) = schedule {
+        dependents.extend(schedule.assignments.iter()
+            .filter(|a| assignment_uses_member(a, item_name, member_id))
+            .map(assignment_dependent));
+    }
+
+    dependents
+}
+
+/// Constraints that check the named curriculum.
+pub fn curriculum_dependents(name: &str, constraints: &[Constraint]) -> Vec<Dependent> {
+    constraints.iter()
+        .filter(|c| matches!(&c.rule, ConstraintRule::CurriculumConflict { curricula } if curricula.iter().any(|n| n == name)))
+        .map(|c| Dependent::Constraint { id: c.id, name: c.name.clone() })
+        .collect()
+}
+
+/// Drops every dependent from the problem data, the constraint list and the
+/// stored schedule.
+pub fn remove_dependents(
+    dependents: &[Dependent],
+    problem_data: &mut ProblemData,
+    constraints: &mut Vec<Constraint>,
+    schedule: Option<&mut Schedule>,
+) {
+    constraints.retain(|c| !dependents.contains(&Dependent::Constraint { id: c.id, name: c.name.clone() }));
+
+    problem_data.curricula.retain(|c| !dependents.contains(&Dependent::Curriculum { name: c.name.clone() }));
+    for curriculum in &mut problem_data.curricula {
+        curriculum.tasks.retain(|task_id| !dependents.contains(&Dependent::CurriculumTask {
+            curriculum: curriculum.name.clone(),
+            task_id: *task_id,
+        }));
+    }
+
+    problem_data.pairings.retain(|p| !dependents.contains(&pairing_dependent(p)));
+    for pairing in &mut problem_data.pairings {
+        let PairingRule::Table { allowed } = &mut pairing.rule else {
+            continue;
+        };
+        for dependent in dependents {
+            let Dependent::PairingMember { task_item, resource_item, item_name, member_id } = dependent else {
+                continue;
+            };
+            if *task_item != pairing.task_item || *resource_item != pairing.resource_item {
+                continue;
+            }
+            if *item_name == pairing.task_item {
+                allowed.retain(|row| row.task_id != *member_id);
+            }
+            if *item_name == pairing.resource_item {
+                for row in allowed.iter_mut() {
+                    row.resource_ids.retain(|id| id != member_id);
+                }
+            }
+        }
+    }
+
+    for dependent in dependents {
+        let Dependent::PreferenceScores { constraint_id, item_name, member_id } = dependent else {
+            continue;
+        };
+        let Some(constraint) = constraints.iter_mut().find(|c| c.id == *constraint_id) else {
+            continue;
+        };
+        if let ConstraintRule::PreferenceMatrix { row_item, column_item, scores, .. } = &mut constraint.rule {
+            scores.retain(|s| !score_involves(row_item, column_item, s, item_name, *member_id));
+        }
+    }
+
+    let time_removed = problem_data.time_dimension.as_ref()
+        .is_some_and(|t| dependents.contains(&Dependent::TimeDimension { item_name: t.item_name.clone() }));
+    if time_removed {
+        problem_data.time_dimension = None;
+    }
+    let space_removed = problem_data.space_dimension.as_ref()
+        .is_some_and(|s| dependents.contains(&Dependent::SpaceDimension { item_name: s.item_name.clone() }));
+    if space_removed {
+        problem_data.space_dimension = None;
+    }
+
+    if let Some(schedule) = schedule {
+        schedule.assignments.retain(|a| !dependents.contains(&assignment_dependent(a)));
+    }
+}
+
+/// Points every reference to a member of `item_name` at its new id in
+/// `mapping`; ids missing from `mapping` are left as they are.
+pub fn remap_member_ids(
+    item_name: &str,
+    mapping: &std::collections::HashMap<ItemId, ItemId>,
+    problem_data: &mut ProblemData,
+    constraints: &mut [Constraint],
+    schedule: Option<&mut Schedule>,
+) {
+    let remap = |id: &mut ItemId| {
+        if let Some(new_id) = mapping.get(id) {
+            *id = *new_id;
+        }
+    };
+
+    for constraint in constraints.iter_mut() {
+        for condition in constraint.rule.conditions_mut() {
+            if condition.item_name != item_name || condition.field_key != "id" || condition.target_field.is_some() {
+                continue;
+            }
+            for target in &mut condition.target_values {
+                if let Some(new_id) = target.parse::<u32>().ok().and_then(|id| mapping.get(&ItemId(id))) {
+                    *target = new_id.0.to_string();
+                }
+            }
+        }
+        if let ConstraintRule::PreferenceMatrix { row_item, column_item, scores, .. } = &mut constraint.rule {
+            for score in scores {
+                if row_item == item_name {
+                    remap(&mut score.row_id);
+                }
+                if column_item == item_name {
+                    remap(&mut score.column_id);
+                }
+            }
+        }
+    }
+
+    for curriculum in problem_data.curricula.iter_mut().filter(|c| c.task_item == item_name) {
+        curriculum.tasks.iter_mut().for_each(remap);
+    }
+
+    for pairing in &mut problem_data.pairings {
+        let PairingRule::Table { allowed } = &mut pairing.rule else {
+            continue;
+        };
+        for row in allowed {
+            if pairing.task_item == item_name {
+                remap(&mut row.task_id);
+            }
+            if pairing.resource_item == item_name {
+                row.resource_ids.iter_mut().for_each(remap);
+            }
+        }
+    }
+
+    if let Some(schedule) = schedule {
+        for assignment in &mut schedule.assignments {
+            if assignment.task_item_name == item_name {
+                remap(&mut assignment.task_id);
+            }
+            if let Some(id) = assignment.resources.get_mut(item_name) {
+                remap(id);
+            }
+        }
+    }
+}
+
+/// Lists every reference in the constraints and the stored schedule that
+/// points at an item, field or member that does not exist.
+pub fn find_broken_references(
+    problem_data: &ProblemData,
+    constraints: &[Constraint],
+    schedule: Option<&Schedule>,
+) -> Vec<BrokenReference> {
+    let mut broken = Vec::new();
+
+    for constraint in constraints {
+        let dependent = Dependent::Constraint { id: constraint.id, name: constraint.name.clone() };
+        let mut report = |reference: String, reason: String| broken.push(BrokenReference {
+            dependent: dependent.clone(),
+            reference,
+            reason,
+        });
+
+        for condition in constraint.rule.conditions() {
+            for (item_name, field_key) in condition.field_refs() {
+                let reference = format!("{}:{}", item_name, field_key);
+                match problem_data.item_categories.get(item_name) {
+                    None => report(reference, format!("item \"{}\" does not exist", item_name)),
+                    Some(item) if !has_field(item, field_key) => {
+                        report(reference, format!("item \"{}\" has no field \"{}\"", item_name, field_key))
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            let Some(item) = problem_data.item_categories.get(&condition.item_name) else {
+                continue;
+            };
+            if condition.field_key == "id" && condition.target_field.is_none() {
+                for target in &condition.target_values {
+                    let exists = target.parse::<u32>().ok()
+                        .is_some_and(|id| item.member(ItemId(id)).is_some());
+                    if !exists {
+                        report(
+                            format!("{}:{}", item.name, target),
+                            format!("item \"{}\" has no member {}", item.name, target),
+                        );
+                    }
+                }
+            }
+        }
+
+        for item_field in constraint.rule.item_field_refs() {
+            let Some((item_name, field_key)) = item_field.split_once(':') else {
+                report(item_field.clone(), "expected the form \"Item:field\"".to_string());
+                continue;
+            };
+            match problem_data.item_categories.get(item_name) {
+                None => report(item_field.clone(), format!("item \"{}\" does not exist", item_name)),
+                Some(item) if !has_field(item, field_key) => report(
+                    item_field.clone(),
+                    format!("item \"{}\" has no field \"{}\"", item_name, field_key),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        for item_name in constraint.rule.item_names() {
+            if !problem_data.item_categories.contains_key(item_name) {
+                report(item_name.clone(), format!("item \"{}\" does not exist", item_name));
+            }
+        }
+
+        if let ConstraintRule::GlobalTemporalPrecedence { temporal_item, temporal_fields, .. } = &constraint.rule {
+            let item_name = temporal_item.as_deref().unwrap_or(&problem_data.time().item_name);
+            if let Some(item) = problem_data.item_categories.get(item_name) {
+                for field_key in temporal_fields.iter().filter(|f| !has_field(item, f)) {
+                    report(
+                        format!("{}:{}", item_name, field_key),
+                        format!("item \"{}\" has no field \"{}\"", item_name, field_key),
+                    );
+                }
+            }
+        }
+
+        if let ConstraintRule::PreferenceMatrix { row_item, column_item, scores, .. } = &constraint.rule {
+            let axes = [(row_item, scores.iter().map(|s| s.row_id).collect::<Vec<_>>()),
+                (column_item, scores.iter().map(|s| s.column_id).collect())];
+            for (item_name, member_ids) in axes {
+                let Some(item) = problem_data.item_categories.get(item_name) else {
+                    continue;
+                };
+                let missing: std::collections::BTreeSet<_> = member_ids.into_iter()
+                    .filter(|id| item.member(*id).is_none())
+                    .collect();
+                for member_id in missing {
+                    report(
+                        format!("{}:{}", item_name, member_id.0),
+                        format!("item \"{}\" has no member {}", item_name, member_id.0),
+                    );
+                }
+            }
+        }
+
+        if let ConstraintRule::CurriculumConflict { curricula } = &constraint.rule {
+            for name in curricula {
+                if !problem_data.curricula.iter().any(|c| c.name == *name) {
+                    report(name.clone(), format!("curriculum \"{}\" does not exist", name));
+                }
+            }
+        }
+    }
+
+    for pairing in &problem_data.pairings {
+        let dependent = pairing_dependent(pairing);
+        let mut report = |reference: String, reason: String| broken.push(BrokenReference {
+            dependent: dependent.clone(),
+            reference,
+            reason,
+        });
+
+        let tasks = problem_data.item_categories.get(&pairing.task_item);
+        let resources = problem_data.item_categories.get(&pairing.resource_item);
+        for (item_name, item) in [(&pairing.task_item, tasks), (&pairing.resource_item, resources)] {
+            if item.is_none() {
+                report(item_name.clone(), format!("item \"{}\" does not exist", item_name));
+            }
+        }
+
+        match &pairing.rule {
+            PairingRule::Table { allowed } => {
+                let members = allowed.iter()
+                    .map(|row| (tasks, row.task_id))
+                    .chain(allowed.iter().flat_map(|row| row.resource_ids.iter().map(|id| (resources, *id))));
+                for (item, member_id) in members {
+                    if let Some(item) = item.filter(|item| item.member(member_id).is_none()) {
+                        report(
+                            format!("{}:{}", item.name, member_id.0),
+                            format!("item \"{}\" has no member {}", item.name, member_id.0),
+                        );
+                    }
+                }
+            }
+            PairingRule::FieldMatch { task_field, resource_field } => {
+                for (item, field_key) in [(tasks, task_field), (resources, resource_field)] {
+                    if let Some(item) = item.filter(|item| !has_field(item, field_key)) {
+                        report(
+                            format!("{}:{}", item.name, field_key),
+                            format!("item \"{}\" has no field \"{}\"", item.name, field_key),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    for curriculum in &problem_data.curricula {
+        let dependent = Dependent::Curriculum { name: curriculum.name.clone() };
+        let Some(item) = problem_data.item_categories.get(&curriculum.task_item) else {
+            broken.push(BrokenReference {
+                dependent,
+                reference: curriculum.task_item.clone(),
+                reason: format!("item \"{}\" does not exist", curriculum.task_item),
+            });
+            continue;
+        };
+        for task_id in &curriculum.tasks {
+            if item.member(*task_id).is_none() {
+                broken.push(BrokenReference {
+                    dependent: dependent.clone(),
+                    reference: format!("{}:{}", item.name, task_id.0),
+                    reason: format!("item \"{}\" has no member {}", item.name, task_id.0),
+                });
+            }
+        }
+    }
+
+    if let Some(time) = &problem_data.time_dimension {
+        let dependent = Dependent::TimeDimension { item_name: time.item_name.clone() };
+        match problem_data.item_categories.get(&time.item_name) {
+            None => broken.push(BrokenReference {
+                dependent,
+                reference: time.item_name.clone(),
+                reason: format!("item \"{}\" does not exist", time.item_name),
+            }),
+            Some(item) => {
+                for field_key in [&time.start_field, &time.end_field] {
+                    if !has_field(item, field_key) {
+                        broken.push(BrokenReference {
+                            dependent: dependent.clone(),
+                            reference: format!("{}:{}", item.name, field_key),
+                            reason: format!("item \"{}\" has no field \"{}\"", item.name, field_key),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    if let Some(space) = &problem_data.space_dimension {
+        if !problem_data.item_categories.contains_key(&space.item_name) {
+            broken.push(BrokenReference {
+                dependent: Dependent::SpaceDimension { item_name: space.item_name.clone() },
+                reference: space.item_name.clone(),
+                reason: format!("item \"{}\" does not exist", space.item_name),
+            });
+        }
+    }
+
+    if let Some(schedule) = schedule {
+        for assignment in &schedule.assignments {
+            let dependent = assignment_dependent(assignment);
+            let members = std::iter::once((&assignment.task_item_name, &assignment.task_id))
+                .chain(assignment.resources.iter());
+
+            for (item_name, member_id) in members {
+                let reference = format!("{}:{}", item_name, member_id.0);
+                match problem_data.item_categories.get(item_name) {
+                    None => broken.push(BrokenReference {
+                        dependent: dependent.clone(),
+                        reference,
+                        reason: format!("item \"{}\" does not exist", item_name),
+                    }),
+                    Some(item) if item.member(*member_id).is_none() => broken.push(BrokenReference {
+                        dependent: dependent.clone(),
+                        reference,
+                        reason: format!("item \"{}\" has no member {}", item_name, member_id.0),
+                    }),
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+
+    broken
+}
+
+fn has_field(item: &Item, field_key: &str) -> bool {
+    field_key == "id" || item.schema.definitions.contains_key(field_key)
+}
+
+fn constraint_mentions_item(constraint: &Constraint, item_name: &str) -> bool {
+    constraint.rule.conditions().iter()
+        .flat_map(|c| c.field_refs())
+        .any(|(item, _)| item == item_name)
+        || constraint.rule.item_field_refs().iter()
+            .any(|f| f.split_once(':').is_some_and(|(item, _)| item == item_name))
+        || constraint.rule.item_names().iter().any(|item| *item == item_name)
+}
+
+fn pairing_dependent(pairing: &ResourcePairing) -> Dependent {
+    Dependent::Pairing {
+        task_item: pairing.task_item.clone(),
+        resource_item: pairing.resource_item.clone(),
+    }
+}
+
+fn table_mentions_member(pairing: &ResourcePairing, item_name: &str, member_id: ItemId) -> bool {
+    let PairingRule::Table { allowed } = &pairing.rule else {
+        return false;
+    };
+    (pairing.task_item == item_name && allowed.iter().any(|row| row.task_id == member_id))
+        || (pairing.resource_item == item_name && allowed.iter().any(|row| row.resource_ids.contains(&member_id)))
+}
+
+fn has_preference_scores(constraint: &Constraint, item_name: &str, member_id: ItemId) -> bool {
+    match &constraint.rule {
+        ConstraintRule::PreferenceMatrix { row_item, column_item, scores, .. } => {
+            scores.iter().any(|s| score_involves(row_item, column_item, s, item_name, member_id))
+        }
+        _ => false,
+    }
+}
+
+fn score_involves(
+    row_item: &str,
+    column_item: &str,
+    score: &PreferenceScore,
+    item_name: &str,
+    member_id: ItemId,
+) -> bool {
+    (row_item == item_name && score.row_id == member_id)
+        || (column_item == item_name && score.column_id == member_id)
+}
+
+fn constraint_mentions_member(constraint: &Constraint, item_name: &str, member_id: ItemId) -> bool {
+    let id = member_id.0.to_string();
+    constraint.rule.conditions().iter().any(|c| {
+        c.item_name == item_name && c.field_key == "id" && c.target_values.contains(&id)
+    })
+}
+
+fn assignment_uses_member(assignment: &Assignment, item_name: &str, member_id: ItemId) -> bool {
+    (assignment.task_item_name == item_name && assignment.task_id == member_id)
+        || assignment.resources.get(item_name) == Some(&member_id)
+}
+
+fn assignment_dependent(assignment: &Assignment) -> Dependent {
+    Dependent::ScheduleAssignment {
+        task_item_name: assignment.task_item_name.clone(),
+        task_id: assignment.task_id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    fn lecturer_one() -> Constraint {
+        constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("Lecturer", "id", "Equal", &["1"])],
+            "logical_op": "And", "mode": "Forbid",
+        } }))
+    }
+
+    fn room_capacity() -> Constraint {
+        constraint(2, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("Room", "capacity", "LessThan", &["30"])],
+            "logical_op": "And", "mode": "Forbid",
+        } }))
+    }
+
+    #[test]
+    fn members_are_referenced_by_id_conditions_and_assignments() {
+        let problem_data = timetable();
+        let constraints = vec![lecturer_one(), room_capacity()];
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 2, 2), lesson(3, 3, 1, 3)]);
+
+        let dependents = member_dependents(&problem_data, "Lecturer", ItemId(1), &constraints, Some(&schedule));
+        assert_eq!(dependents, [
+            Dependent::Constraint { id: ConstraintId(1), name: "Constraint 1".to_string() },
+            Dependent::ScheduleAssignment { task_item_name: "Course".to_string(), task_id: ItemId(1) },
+            Dependent::ScheduleAssignment { task_item_name: "Course".to_string(), task_id: ItemId(3) },
+        ]);
+        assert!(member_dependents(&problem_data, "Room", ItemId(1), &constraints, None).is_empty());
+
+        let dependents = item_dependents(&problem_data, "Room", &constraints, Some(&schedule));
+        assert_eq!(dependents.len(), 4);
+        assert_eq!(dependents[0], Dependent::Constraint { id: ConstraintId(2), name: "Constraint 2".to_string() });
+    }
+
+    #[test]
+    fn cascading_removes_dependents_only() {
+        let mut problem_data = timetable();
+        let mut constraints = vec![lecturer_one(), room_capacity()];
+        let mut schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 2, 2)]);
+
+        let dependents = member_dependents(&problem_data, "Lecturer", ItemId(1), &constraints, Some(&schedule));
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, Some(&mut schedule));
+
+        let ids: Vec<ConstraintId> = constraints.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [ConstraintId(2)]);
+        let tasks: Vec<ItemId> = schedule.assignments.iter().map(|a| a.task_id).collect();
+        assert_eq!(tasks, [ItemId(2)]);
+    }
+
+    #[test]
+    fn broken_references_name_the_missing_field_or_member() {
+        let mut problem_data = timetable();
+        problem_data.item_categories.get_mut("Room").unwrap().schema.definitions.remove("capacity");
+        problem_data.item_categories.get_mut("Lecturer").unwrap().members.remove(0);
+        let constraints = vec![lecturer_one(), room_capacity()];
+        let schedule = Schedule::new(vec![lesson(1, 1, 1, 1), lesson(2, 2, 2, 2)]);
+
+        let broken: Vec<(Dependent, String)> = find_broken_references(&problem_data, &constraints, Some(&schedule))
+            .into_iter()
+            .map(|b| (b.dependent, b.reference))
+            .collect();
+        assert_eq!(broken, [
+            (Dependent::Constraint { id: ConstraintId(1), name: "Constraint 1".to_string() }, "Lecturer:1".to_string()),
+            (Dependent::Constraint { id: ConstraintId(2), name: "Constraint 2".to_string() }, "Room:capacity".to_string()),
+            (Dependent::ScheduleAssignment { task_item_name: "Course".to_string(), task_id: ItemId(1) }, "Lecturer:1".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn curricula_lose_deleted_tasks_and_go_with_their_item() {
+        let mut problem_data = timetable();
+        problem_data.curricula = vec![curriculum("Year 1", &[1, 2], None)];
+        let mut constraints = vec![constraint(1, json!({ "CurriculumConflict": { "curricula": ["Year 1"] } }))];
+
+        let dependents = member_dependents(&problem_data, "Course", ItemId(2), &constraints, None);
+        assert_eq!(dependents, [Dependent::CurriculumTask { curriculum: "Year 1".to_string(), task_id: ItemId(2) }]);
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, None);
+        assert_eq!(problem_data.curricula[0].tasks, [ItemId(1)]);
+
+        let dependents = item_dependents(&problem_data, "Course", &constraints, None);
+        assert_eq!(dependents, [Dependent::Curriculum { name: "Year 1".to_string() }]);
+        assert_eq!(curriculum_dependents("Year 1", &constraints).len(), 1);
+    }
+
+    #[test]
+    fn preference_scores_of_deleted_members_are_dropped() {
+        let mut problem_data = timetable();
+        let mut constraints = vec![preferences(1, 0, &[(1, 1, 5), (2, 1, 3), (1, 2, 4)])];
+
+        let dependents = member_dependents(&problem_data, "Lecturer", ItemId(1), &constraints, None);
+        assert_eq!(dependents, [Dependent::PreferenceScores {
+            constraint_id: ConstraintId(1), item_name: "Lecturer".to_string(), member_id: ItemId(1),
+        }]);
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, None);
+
+        let ConstraintRule::PreferenceMatrix { scores, .. } = &constraints[0].rule else {
+            panic!("not a preference matrix");
+        };
+        let cells: Vec<(ItemId, ItemId)> = scores.iter().map(|s| (s.row_id, s.column_id)).collect();
+        assert_eq!(cells, [(ItemId(2), ItemId(1))]);
+    }
+
+    #[test]
+    fn pairing_tables_lose_deleted_members_and_go_with_their_item() {
+        let mut problem_data = timetable();
+        problem_data.pairings = vec![lecturer_table(&[(1, &[1, 2]), (2, &[2])])];
+        let mut constraints = Vec::new();
+
+        let dependents = member_dependents(&problem_data, "Course", ItemId(2), &constraints, None);
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, None);
+        let dependents = member_dependents(&problem_data, "Lecturer", ItemId(1), &constraints, None);
+        assert_eq!(dependents, [Dependent::PairingMember {
+            task_item: "Course".to_string(), resource_item: "Lecturer".to_string(),
+            item_name: "Lecturer".to_string(), member_id: ItemId(1),
+        }]);
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, None);
+        assert_eq!(problem_data.allowed_resources("Course", ItemId(1), "Lecturer"), [ItemId(2)]);
+        assert_eq!(problem_data.allowed_resources("Course", ItemId(2), "Lecturer"), [ItemId(1), ItemId(2)]);
+
+        let dependents = item_dependents(&problem_data, "Lecturer", &constraints, None);
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, None);
+        assert!(problem_data.pairings.is_empty());
+    }
+
+    #[test]
+    fn deleting_a_dimension_item_falls_back_to_the_default() {
+        let mut problem_data = timetable();
+        problem_data.space_dimension = Some(SpaceDimension { item_name: "Lecturer".to_string() });
+        let mut constraints = Vec::new();
+
+        let dependents = item_dependents(&problem_data, "Lecturer", &constraints, None);
+        assert_eq!(dependents, [Dependent::SpaceDimension { item_name: "Lecturer".to_string() }]);
+        assert!(item_dependents(&problem_data, "TimeSlot", &constraints, None).is_empty());
+
+        remove_dependents(&dependents, &mut problem_data, &mut constraints, None);
+        assert!(problem_data.space_dimension.is_none());
+        assert_eq!(problem_data.space().item_name, "Room");
+    }
+}
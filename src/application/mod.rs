@@ -1,5 +1,15 @@
+pub mod compiled;
 pub mod constraint_evaluator;
 pub mod algorithm;
+pub mod schema_migration;
+pub mod integrity;
+pub mod constraint_validator;
+pub mod timeslot_generator;
 
+pub use compiled::CompiledProblem;
 pub use constraint_evaluator::*;
 pub use algorithm::*;
+pub use schema_migration::*;
+pub use integrity::*;
+pub use constraint_validator::*;
+pub use timeslot_generator::*;
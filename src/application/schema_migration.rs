@@ -0,0 +1,451 @@
+use crate::domain::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SchemaChange {
+    AddField {
+        field_name: String,
+        field_type: FieldType,
+        is_required: bool,
+        default: Option<Value>,
+    },
+    RenameField {
+        from: String,
+        to: String,
+    },
+    ChangeFieldType {
+        field_name: String,
+        field_type: FieldType,
+    },
+    DropField {
+        field_name: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MigrationIssue {
+    pub member_id: Option<ItemId>,
+    pub constraint_name: Option<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub migrated_members: usize,
+    pub updated_constraints: Vec<String>,
+    /// Pairings whose matched fields were renamed, as "Task -> Resource".
+    pub updated_pairings: Vec<String>,
+    pub issues: Vec<MigrationIssue>,
+}
+
+/// Applies `changes` in order to the schema and members of `item_name` and
+/// rewrites constraint, pairing and time dimension references to renamed
+/// fields.
+///
+/// A change that cannot apply to the schema at all (unknown field, name
+/// clash) rejects the whole batch and leaves everything untouched. Values or
+/// constraints that cannot follow a change are kept as they are and listed in
+/// the report instead.
+pub fn migrate_item(
+    problem_data: &mut ProblemData,
+    constraints: &mut Vec<Constraint>,
+    item_name: &str,
+    changes: &[SchemaChange],
+) -> Result<MigrationReport, String> {
+    let mut item = problem_data.item_categories.get(item_name)
+        .cloned()
+        .ok_or_else(|| format!("Item \"{}\" not found", item_name))?;
+    let mut migrated_constraints = constraints.clone();
+    let mut migrated_pairings = problem_data.pairings.clone();
+    let mut migrated_time = problem_data.time().clone();
+    let time_item = migrated_time.item_name.clone();
+    let mut report = MigrationReport::default();
+    let mut touched_members = std::collections::HashSet::new();
+    let mut touched_constraints = std::collections::HashSet::new();
+    let mut touched_pairings = std::collections::HashSet::new();
+
+    for change in changes {
+        match change {
+            SchemaChange::AddField { field_name, field_type, is_required, default } => {
+                check_not_reserved(field_name)?;
+                if item.schema.definitions.contains_key(field_name) {
+                    return Err(format!("Field \"{}\" already exists", field_name));
+                }
+                let default = match default {
+                    Some(value) => Some(value.convert_to(field_type).ok_or_else(|| {
+                        format!("Default for \"{}\" is not a valid {:?}", field_name, field_type)
+                    })?),
+                    None => None,
+                };
+
+                item.schema.definitions.insert(field_name.clone(), FieldSchema {
+                    field_name: field_name.clone(),
+                    field_type: field_type.clone(),
+                    is_required: *is_required,
+                });
+
+                for member in &mut item.members {
+                    if member.fields.contains_key(field_name) {
+                        continue;
+                    }
+                    match &default {
+                        Some(value) => {
+                            member.fields.insert(field_name.clone(), value.clone());
+                            touched_members.insert(member.id);
+                        }
+                        None if *is_required => report.issues.push(MigrationIssue {
+                            member_id: Some(member.id),
+                            constraint_name: None,
+                            message: format!("Required field \"{}\" has no value and no default", field_name),
+                        }),
+                        None => {}
+                    }
+                }
+            }
+            SchemaChange::RenameField { from, to } => {
+                check_not_reserved(from)?;
+                check_not_reserved(to)?;
+                if item.schema.definitions.contains_key(to) {
+                    return Err(format!("Field \"{}\" already exists", to));
+                }
+                let mut definition = item.schema.definitions.remove(from)
+                    .ok_or_else(|| format!("Field \"{}\" not found", from))?;
+                definition.field_name = to.clone();
+                item.schema.definitions.insert(to.clone(), definition);
+
+                for member in &mut item.members {
+                    if let Some(value) = member.fields.remove(from) {
+                        member.fields.insert(to.clone(), value);
+                        touched_members.insert(member.id);
+                    }
+                }
+
+                let old_ref = format!("{}:{}", item_name, from);
+                let new_ref = format!("{}:{}", item_name, to);
+                for constraint in &mut migrated_constraints {
+                    let mut changed = false;
+                    for condition in constraint.rule.conditions_mut() {
+                        if condition.item_name == item_name && condition.field_key == *from {
+                            condition.field_key = to.clone();
+                            changed = true;
+                        }
+                        if let Some(operand) = &mut condition.target_field {
+                            if operand.item_name == item_name && operand.field_key == *from {
+                                operand.field_key = to.clone();
+                                changed = true;
+                            }
+                        }
+                    }
+                    for item_field in constraint.rule.item_field_refs_mut() {
+                        if *item_field == old_ref {
+                            *item_field = new_ref.clone();
+                            changed = true;
+                        }
+                    }
+                    for field in temporal_fields_mut(&mut constraint.rule, item_name, &time_item) {
+                        if field == from {
+                            *field = to.clone();
+                            changed = true;
+                        }
+                    }
+                    if changed {
+                        touched_constraints.insert(constraint.name.clone());
+                    }
+                }
+
+                if migrated_time.item_name == item_name {
+                    let time = &mut migrated_time;
+                    for field in [&mut time.day_field, &mut time.start_field, &mut time.end_field] {
+                        if field == from {
+                            *field = to.clone();
+                        }
+                    }
+                }
+
+                for (index, pairing) in migrated_pairings.iter_mut().enumerate() {
+                    if let PairingRule::FieldMatch { task_field, resource_field } = &mut pairing.rule {
+                        for (pairing_item, field) in [(&pairing.task_item, task_field), (&pairing.resource_item, resource_field)] {
+                            if pairing_item == item_name && field == from {
+                                *field = to.clone();
+                                touched_pairings.insert(index);
+                            }
+                        }
+                    }
+                }
+            }
+            SchemaChange::ChangeFieldType { field_name, field_type } => {
+                check_not_reserved(field_name)?;
+                let definition = item.schema.definitions.get_mut(field_name)
+                    .ok_or_else(|| format!("Field \"{}\" not found", field_name))?;
+                definition.field_type = field_type.clone();
+
+                for member in &mut item.members {
+                    let Some(value) = member.fields.get_mut(field_name) else {
+                        continue;
+                    };
+                    if value.field_type() == *field_type {
+                        continue;
+                    }
+                    match value.convert_to(field_type) {
+                        Some(converted) => {
+                            *value = converted;
+                            touched_members.insert(member.id);
+                        }
+                        None => report.issues.push(MigrationIssue {
+                            member_id: Some(member.id),
+                            constraint_name: None,
+                            message: format!("Value of \"{}\" cannot be converted to {:?}", field_name, field_type),
+                        }),
+                    }
+                }
+
+                for constraint in &migrated_constraints {
+                    for condition in constraint.rule.conditions() {
+                        if condition.item_name != item_name || condition.field_key != *field_name {
+                            continue;
+                        }
+                        let unconvertible = condition.target_values.iter()
+                            .any(|t| Value::String(t.clone()).convert_to(field_type).is_none());
+                        if unconvertible {
+                            report.issues.push(MigrationIssue {
+                                member_id: None,
+                                constraint_name: Some(constraint.name.clone()),
+                                message: format!("Target values for \"{}\" are not valid {:?} values", field_name, field_type),
+                            });
+                        }
+                    }
+                }
+            }
+            SchemaChange::DropField { field_name } => {
+                check_not_reserved(field_name)?;
+                if item.schema.definitions.remove(field_name).is_none() {
+                    return Err(format!("Field \"{}\" not found", field_name));
+                }
+                for member in &mut item.members {
+                    if member.fields.remove(field_name).is_some() {
+                        touched_members.insert(member.id);
+                    }
+                }
+
+                let dropped_ref = format!("{}:{}", item_name, field_name);
+                for constraint in &migrated_constraints {
+                    let in_conditions = constraint.rule.conditions().iter()
+                        .flat_map(|c| c.field_refs())
+                        .any(|(item, field)| item == item_name && field == field_name);
+                    let in_item_fields = constraint.rule.item_field_refs().iter()
+                        .any(|f| **f == dropped_ref);
+                    let in_temporal_fields = temporal_fields(&constraint.rule, item_name, &time_item).iter()
+                        .any(|f| f == field_name);
+                    if in_conditions || in_item_fields || in_temporal_fields {
+                        report.issues.push(MigrationIssue {
+                            member_id: None,
+                            constraint_name: Some(constraint.name.clone()),
+                            message: format!("Constraint still refers to dropped field \"{}\"", field_name),
+                        });
+                    }
+                }
+
+                let time = &migrated_time;
+                let in_time = [&time.day_field, &time.start_field, &time.end_field].contains(&field_name);
+                if time.item_name == item_name && in_time {
+                    report.issues.push(MigrationIssue {
+                        member_id: None,
+                        constraint_name: None,
+                        message: format!("Time dimension still reads dropped field \"{}\"", field_name),
+                    });
+                }
+
+                for pairing in &migrated_pairings {
+                    if let PairingRule::FieldMatch { task_field, resource_field } = &pairing.rule {
+                        let uses_field = (pairing.task_item == item_name && task_field == field_name)
+                            || (pairing.resource_item == item_name && resource_field == field_name);
+                        if uses_field {
+                            report.issues.push(MigrationIssue {
+                                member_id: None,
+                                constraint_name: None,
+                                message: format!(
+                                    "Pairing {} -> {} still matches on dropped field \"{}\"",
+                                    pairing.task_item, pairing.resource_item, field_name
+                                ),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    report.migrated_members = touched_members.len();
+    report.updated_constraints = migrated_constraints.iter()
+        .filter(|c| touched_constraints.contains(&c.name))
+        .map(|c| c.name.clone())
+        .collect();
+    report.updated_pairings = migrated_pairings.iter()
+        .enumerate()
+        .filter(|(index, _)| touched_pairings.contains(index))
+        .map(|(_, p)| format!("{} -> {}", p.task_item, p.resource_item))
+        .collect();
+
+    problem_data.item_categories.insert(item_name.to_string(), item);
+    problem_data.pairings = migrated_pairings;
+    if migrated_time != *problem_data.time() {
+        problem_data.time_dimension = Some(migrated_time);
+    }
+    *constraints = migrated_constraints;
+
+    Ok(report)
+}
+
+/// The temporal fields a precedence rule reads from `item_name`, if any.
+fn temporal_fields<'a>(rule: &'a ConstraintRule, item_name: &str, time_item: &str) -> &'a [String] {
+    match rule {
+        ConstraintRule::GlobalTemporalPrecedence { temporal_item, temporal_fields, .. }
+            if temporal_item.as_deref().unwrap_or(time_item) == item_name => temporal_fields,
+        _ => &[],
+    }
+}
+
+fn temporal_fields_mut<'a>(rule: &'a mut ConstraintRule, item_name: &str, time_item: &str) -> &'a mut [String] {
+    match rule {
+        ConstraintRule::GlobalTemporalPrecedence { temporal_item, temporal_fields, .. }
+            if temporal_item.as_deref().unwrap_or(time_item) == item_name => temporal_fields,
+        _ => &mut [],
+    }
+}
+
+fn check_not_reserved(field_name: &str) -> Result<(), String> {
+    if field_name == "id" {
+        Err("Field \"id\" is reserved".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    fn rename(from: &str, to: &str) -> SchemaChange {
+        SchemaChange::RenameField { from: from.to_string(), to: to.to_string() }
+    }
+
+    #[test]
+    fn renaming_a_field_rewrites_constraints_and_pairings() {
+        let mut problem_data = timetable();
+        problem_data.pairings = vec![ResourcePairing {
+            task_item: "Course".to_string(),
+            resource_item: "Room".to_string(),
+            rule: PairingRule::FieldMatch { task_field: "students".to_string(), resource_field: "capacity".to_string() },
+        }];
+        let mut constraints = vec![
+            constraint(1, json!({ "MultiAssignmentCheck": {
+                "conditions": [condition("Room", "capacity", "LessThan", &["30"])],
+                "logical_op": "And", "mode": "Forbid",
+            } })),
+            constraint(2, json!({ "GlobalAllDifferent": {
+                "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id",
+            } })),
+        ];
+
+        let report = migrate_item(&mut problem_data, &mut constraints, "Room", &[rename("capacity", "seats")]).unwrap();
+
+        assert_eq!(report.migrated_members, 3);
+        assert_eq!(report.updated_constraints, ["Constraint 1"]);
+        assert_eq!(report.updated_pairings, ["Course -> Room"]);
+        assert!(report.issues.is_empty());
+        assert_eq!(constraints[0].rule.conditions()[0].field_key, "seats");
+        assert!(matches!(&problem_data.pairings[0].rule, PairingRule::FieldMatch { resource_field, .. } if resource_field == "seats"));
+        let room = problem_data.item_categories["Room"].member(ItemId(1)).unwrap();
+        assert_eq!(room.fields.get("seats"), Some(&Value::Number(20)));
+        assert!(!room.fields.contains_key("capacity"));
+    }
+
+    #[test]
+    fn added_fields_take_the_default_or_are_reported_missing() {
+        let mut problem_data = timetable();
+        let mut constraints = Vec::new();
+        let add = |field: &str, is_required: bool, default: Option<Value>| SchemaChange::AddField {
+            field_name: field.to_string(), field_type: FieldType::Integer, is_required, default,
+        };
+        let changes = [
+            add("floor", true, Some(text("2"))),
+            add("projectors", true, None),
+            add("windows", false, None),
+        ];
+
+        let report = migrate_item(&mut problem_data, &mut constraints, "Room", &changes).unwrap();
+
+        assert_eq!(report.migrated_members, 3);
+        let missing: Vec<Option<ItemId>> = report.issues.iter().map(|issue| issue.member_id).collect();
+        assert_eq!(missing, [Some(ItemId(1)), Some(ItemId(2)), Some(ItemId(3))]);
+        let rooms = &problem_data.item_categories["Room"];
+        assert!(rooms.members.iter().all(|m| m.fields.get("floor") == Some(&Value::Number(2))));
+        assert!(rooms.schema.definitions.contains_key("windows"));
+    }
+
+    #[test]
+    fn changed_types_convert_values_or_report_them() {
+        let mut problem_data = timetable();
+        let rooms = problem_data.item_categories.get_mut("Room").unwrap();
+        rooms.members[0].fields.insert("capacity".to_string(), text("20"));
+        rooms.members[1].fields.insert("capacity".to_string(), text("sixty"));
+        let mut constraints = vec![constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("Room", "building", "Equal", &["North"])],
+            "logical_op": "And", "mode": "Forbid",
+        } }))];
+        let change = |field: &str| SchemaChange::ChangeFieldType { field_name: field.to_string(), field_type: FieldType::Integer };
+
+        let report = migrate_item(&mut problem_data, &mut constraints, "Room", &[change("capacity"), change("building")]).unwrap();
+
+        let rooms = &problem_data.item_categories["Room"];
+        assert_eq!(rooms.members[0].fields["capacity"], Value::Number(20));
+        assert_eq!(rooms.members[1].fields["capacity"], text("sixty"));
+        let messages: Vec<(Option<ItemId>, Option<&str>)> = report.issues.iter()
+            .map(|issue| (issue.member_id, issue.constraint_name.as_deref()))
+            .collect();
+        // Room 2's capacity, every building and the constraint's target "North"
+        assert_eq!(messages, [
+            (Some(ItemId(2)), None),
+            (Some(ItemId(1)), None),
+            (Some(ItemId(2)), None),
+            (Some(ItemId(3)), None),
+            (None, Some("Constraint 1")),
+        ]);
+    }
+
+    #[test]
+    fn dropped_fields_still_in_use_are_reported() {
+        let mut problem_data = timetable();
+        let mut constraints = vec![constraint(1, json!({ "GlobalConsistency": {
+            "group_item_field": "Course:module", "resource_item_field": "Room:id", "max_distinct": 1,
+            "scope_conditions": null,
+        } }))];
+        let drop = SchemaChange::DropField { field_name: "module".to_string() };
+
+        let report = migrate_item(&mut problem_data, &mut constraints, "Course", &[drop]).unwrap();
+
+        assert_eq!(report.migrated_members, 4);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].constraint_name.as_deref(), Some("Constraint 1"));
+        assert!(problem_data.item_categories["Course"].members.iter().all(|m| !m.fields.contains_key("module")));
+    }
+
+    #[test]
+    fn a_change_that_cannot_apply_rejects_the_whole_batch() {
+        let mut problem_data = timetable();
+        let mut constraints = Vec::new();
+
+        let result = migrate_item(&mut problem_data, &mut constraints, "Room", &[rename("capacity", "seats"), rename("floor", "level")]);
+        assert_eq!(result.unwrap_err(), "Field \"floor\" not found");
+        assert!(problem_data.item_categories["Room"].schema.definitions.contains_key("capacity"));
+
+        let result = migrate_item(&mut problem_data, &mut constraints, "Room", &[rename("capacity", "name")]);
+        assert_eq!(result.unwrap_err(), "Field \"name\" already exists");
+        let result = migrate_item(&mut problem_data, &mut constraints, "Room", &[rename("id", "key")]);
+        assert_eq!(result.unwrap_err(), "Field \"id\" is reserved");
+        let result = migrate_item(&mut problem_data, &mut constraints, "Hall", &[]);
+        assert_eq!(result.unwrap_err(), "Item \"Hall\" not found");
+    }
+}
@@ -0,0 +1,405 @@
+use super::constraint_validator::validate_dimensions;
+use crate::domain::{time, *};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A weekly calendar: every listed weekday is cut into slots of
+/// `slot_minutes` between `day_start` and `day_end`, with `break_minutes`
+/// between slots and nothing inside an excluded period.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SlotTemplate {
+    pub weekdays: Vec<String>,
+    pub day_start: String,
+    pub day_end: String,
+    pub slot_minutes: u32,
+    #[serde(default)]
+    pub break_minutes: u32,
+    #[serde(default)]
+    pub excluded: Vec<ExcludedPeriod>,
+}
+
+/// A period no slot may overlap, e.g. lunch from 12:00 to 13:30; on every
+/// weekday unless `weekday` is set.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExcludedPeriod {
+    #[serde(default)]
+    pub weekday: Option<String>,
+    pub start: String,
+    pub end: String,
+}
+
+/// What generating slots changes: the members the time item ends up with,
+/// the new id of every existing slot that is kept, and the existing slots
+/// that go away.
+#[derive(Clone, Debug)]
+pub struct SlotPlan {
+    pub item: Item,
+    pub renumbered: HashMap<ItemId, ItemId>,
+    pub removed: Vec<ItemId>,
+    pub created: usize,
+}
+
+struct Slot {
+    weekday: u32,
+    start: u32,
+    end: u32,
+}
+
+/// The slots `template` describes, ordered by weekday and start.
+fn generate_slots(template: &SlotTemplate) -> Result<Vec<Slot>, Vec<String>> {
+    let mut errors = Vec::new();
+
+    if template.weekdays.is_empty() {
+        errors.push("weekdays: must not be empty".to_string());
+    }
+    let mut weekdays = Vec::new();
+    for (index, day) in template.weekdays.iter().enumerate() {
+        match time::weekday_index(day) {
+            None => errors.push(format!("weekdays[{}]: \"{}\" is not a weekday", index, day)),
+            Some(weekday) if weekdays.contains(&weekday) => {
+                errors.push(format!("weekdays[{}]: \"{}\" is listed twice", index, day));
+            }
+            Some(weekday) => weekdays.push(weekday),
+        }
+    }
+    weekdays.sort();
+
+    let clock = |location: &str, value: &str, errors: &mut Vec<String>| {
+        let minutes = time::parse_clock(value);
+        if minutes.is_none() {
+            errors.push(format!("{}: \"{}\" is not a time", location, value));
+        }
+        minutes
+    };
+    let day_start = clock("day_start", &template.day_start, &mut errors);
+    let day_end = clock("day_end", &template.day_end, &mut errors);
+    if let (Some(start), Some(end)) = (day_start, day_end) {
+        if start >= end {
+            errors.push("day_end: must be after day_start".to_string());
+        } else if template.slot_minutes > end - start {
+            errors.push("slot_minutes: longer than the day".to_string());
+        }
+    }
+    if template.slot_minutes == 0 {
+        errors.push("slot_minutes: must be at least 1".to_string());
+    }
+    if template.break_minutes >= 24 * 60 {
+        errors.push("break_minutes: must be shorter than a day".to_string());
+    }
+
+    let mut excluded = Vec::new();
+    for (index, period) in template.excluded.iter().enumerate() {
+        let location = format!("excluded[{}]", index);
+        let weekday = match &period.weekday {
+            Some(day) => match time::weekday_index(day) {
+                Some(weekday) => Some(weekday),
+                None => {
+                    errors.push(format!("{}: \"{}\" is not a weekday", location, day));
+                    continue;
+                }
+            },
+            None => None,
+        };
+        let start = clock(&location, &period.start, &mut errors);
+        let end = clock(&location, &period.end, &mut errors);
+        match (start, end) {
+            (Some(start), Some(end)) if start < end => excluded.push((weekday, start, end)),
+            (Some(_), Some(_)) => errors.push(format!("{}: end must be after start", location)),
+            _ => {}
+        }
+    }
+
+    let (Some(day_start), Some(day_end)) = (day_start, day_end) else {
+        return Err(errors);
+    };
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut slots = Vec::new();
+    for weekday in weekdays {
+        let mut start = day_start;
+        while start + template.slot_minutes <= day_end {
+            let end = start + template.slot_minutes;
+            // Resume right after the latest exclusion the slot runs into
+            let blocked_until = excluded.iter()
+                .filter(|(day, from, to)| day.is_none_or(|d| d == weekday) && *from < end && start < *to)
+                .map(|(_, _, to)| *to)
+                .max();
+            match blocked_until {
+                Some(to) => start = to,
+                None => {
+                    slots.push(Slot { weekday, start, end });
+                    start = end + template.break_minutes;
+                }
+            }
+        }
+    }
+
+    if slots.is_empty() {
+        return Err(vec!["slot_minutes: no slot fits into the day".to_string()]);
+    }
+    Ok(slots)
+}
+
+/// Works out the time item's members for `template` without changing
+/// anything.
+///
+/// Slots that already exist, going by weekday and start, are never
+/// duplicated. With `replace` the item ends up with exactly the generated
+/// slots, numbered from 1 in weekday and start order; existing slots keep
+/// their other fields under their new id and the rest are removed. Without
+/// it the missing slots are added after the existing ones.
+pub fn plan_slots(template: &SlotTemplate, replace: bool, problem_data: &ProblemData) -> Result<SlotPlan, Vec<String>> {
+    let slots = generate_slots(template)?;
+    let dimension = problem_data.time();
+
+    let mut item = match problem_data.item_categories.get(&dimension.item_name) {
+        Some(item) => {
+            validate_dimensions(Some(dimension), None, problem_data)?;
+            if !item.schema.definitions.contains_key(&dimension.day_field) {
+                return Err(vec![format!(
+                    "time_dimension: item \"{}\" has no field \"{}\"",
+                    item.name, dimension.day_field
+                )]);
+            }
+            item.clone()
+        }
+        None => time_item(dimension),
+    };
+
+    let key = |member: &Member| {
+        let day = match member.fields.get(&dimension.day_field) {
+            Some(Value::String(day)) | Some(Value::Date(day)) => time::weekday_index(day),
+            _ => None,
+        };
+        let start = match member.fields.get(&dimension.start_field) {
+            Some(Value::Date(start)) => time::parse_clock(start),
+            _ => None,
+        };
+        day.zip(start)
+    };
+    let mut existing: HashMap<(u32, u32), &Member> = HashMap::new();
+    for member in &item.members {
+        if let Some(key) = key(member) {
+            existing.entry(key).or_insert(member);
+        }
+    }
+
+    let slot_member = |id: ItemId, slot: &Slot, template: Option<&Member>| {
+        let mut member = template.cloned().unwrap_or_else(|| Member {
+            id,
+            fields: HashMap::new(),
+            availability: Vec::new(),
+        });
+        member.id = id;
+        let day = time::weekday_name(slot.weekday).unwrap_or_default();
+        member.fields.insert(dimension.day_field.clone(), Value::String(day.to_string()));
+        member.fields.insert(dimension.start_field.clone(), Value::Date(time::format_clock(slot.start)));
+        member.fields.insert(dimension.end_field.clone(), Value::Date(time::format_clock(slot.end)));
+        member
+    };
+
+    let mut renumbered = HashMap::new();
+    let mut removed = Vec::new();
+    let created;
+    if replace {
+        let mut members = Vec::new();
+        for (index, slot) in slots.iter().enumerate() {
+            let id = ItemId(index as u32 + 1);
+            let old = existing.get(&(slot.weekday, slot.start)).copied();
+            if let Some(old) = old {
+                renumbered.insert(old.id, id);
+            }
+            members.push(slot_member(id, slot, old));
+        }
+        created = members.len() - renumbered.len();
+        removed = item.members.iter()
+            .map(|m| m.id)
+            .filter(|id| !renumbered.contains_key(id))
+            .collect();
+        item.members = members;
+    } else {
+        let missing: Vec<&Slot> = slots.iter()
+            .filter(|slot| !existing.contains_key(&(slot.weekday, slot.start)))
+            .collect();
+        for slot in &missing {
+            let id = item.allocate_member_id()
+                .ok_or_else(|| vec![format!("item \"{}\" has no member IDs left", item.name)])?;
+            item.members.push(slot_member(id, slot, None));
+        }
+        created = missing.len();
+    }
+
+    Ok(SlotPlan { item, renumbered, removed, created })
+}
+
+/// An empty time item with the dimension's day, start and end fields.
+fn time_item(dimension: &TimeDimension) -> Item {
+    let field = |name: &String, field_type: FieldType| {
+        (name.clone(), FieldSchema { field_name: name.clone(), field_type, is_required: true })
+    };
+    Item {
+        name: dimension.item_name.clone(),
+        item_set_type: SetType::E_Set,
+        members: Vec::new(),
+        last_member_id: 0,
+        schema: Schema {
+            definitions: HashMap::from([
+                field(&dimension.day_field, FieldType::Text),
+                field(&dimension.start_field, FieldType::DateTime),
+                field(&dimension.end_field, FieldType::DateTime),
+            ]),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::remap_member_ids;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    fn template(weekdays: &[&str], day_start: &str, day_end: &str, slot_minutes: u32) -> SlotTemplate {
+        SlotTemplate {
+            weekdays: weekdays.iter().map(|d| d.to_string()).collect(),
+            day_start: day_start.to_string(),
+            day_end: day_end.to_string(),
+            slot_minutes,
+            break_minutes: 0,
+            excluded: Vec::new(),
+        }
+    }
+
+    fn excluded(weekday: Option<&str>, start: &str, end: &str) -> ExcludedPeriod {
+        ExcludedPeriod { weekday: weekday.map(str::to_string), start: start.to_string(), end: end.to_string() }
+    }
+
+    /// Weekday, start and end of every generated slot, as text.
+    fn slots(template: &SlotTemplate) -> Vec<(String, String, String)> {
+        generate_slots(template).unwrap().iter()
+            .map(|slot| (
+                time::weekday_name(slot.weekday).unwrap().to_string(),
+                time::format_clock(slot.start),
+                time::format_clock(slot.end),
+            ))
+            .collect()
+    }
+
+    fn slot(day: &str, start: &str, end: &str) -> (String, String, String) {
+        (day.to_string(), start.to_string(), end.to_string())
+    }
+
+    #[test]
+    fn lunch_is_left_out_and_slots_resume_after_it() {
+        let mut lunch = template(&["Monday"], "11:00", "15:00", 60);
+        lunch.excluded.push(excluded(None, "12:00", "12:30"));
+
+        assert_eq!(slots(&lunch), vec![
+            slot("Monday", "11:00", "12:00"),
+            slot("Monday", "12:30", "13:30"),
+            slot("Monday", "13:30", "14:30"),
+        ]);
+    }
+
+    #[test]
+    fn exclusions_for_one_weekday_leave_the_others_alone() {
+        let mut friday_afternoon = template(&["friday", "Thursday"], "13:00", "15:00", 60);
+        friday_afternoon.excluded.push(excluded(Some("Friday"), "14:00", "15:00"));
+
+        assert_eq!(slots(&friday_afternoon), vec![
+            slot("Thursday", "13:00", "14:00"),
+            slot("Thursday", "14:00", "15:00"),
+            slot("Friday", "13:00", "14:00"),
+        ]);
+    }
+
+    #[test]
+    fn breaks_separate_consecutive_slots() {
+        let mut with_break = template(&["Monday"], "08:00", "11:00", 45);
+        with_break.break_minutes = 15;
+
+        assert_eq!(slots(&with_break), vec![
+            slot("Monday", "08:00", "08:45"),
+            slot("Monday", "09:00", "09:45"),
+            slot("Monday", "10:00", "10:45"),
+        ]);
+    }
+
+    #[test]
+    fn oversized_slots_and_breaks_are_rejected() {
+        let mut huge = template(&["Monday"], "08:00", "10:00", u32::MAX);
+        huge.break_minutes = u32::MAX;
+
+        let errors = generate_slots(&huge).err().unwrap();
+        assert!(errors.contains(&"slot_minutes: longer than the day".to_string()));
+        assert!(errors.contains(&"break_minutes: must be shorter than a day".to_string()));
+    }
+
+    #[test]
+    fn invalid_templates_report_every_problem() {
+        let mut invalid = template(&["Moonday", "Monday", "monday"], "10:00", "9:00", 0);
+        invalid.excluded.push(excluded(None, "13:00", "12:00"));
+
+        let errors = generate_slots(&invalid).err().unwrap();
+        assert_eq!(errors, vec![
+            "weekdays[0]: \"Moonday\" is not a weekday",
+            "weekdays[2]: \"monday\" is listed twice",
+            "day_end: must be after day_start",
+            "slot_minutes: must be at least 1",
+            "excluded[0]: end must be after start",
+        ]);
+    }
+
+    #[test]
+    fn appending_only_adds_missing_slots() {
+        let problem_data = timetable();
+        let plan = plan_slots(&template(&["Monday"], "08:00", "11:00", 60), false, &problem_data).unwrap();
+
+        // 08:00 and 09:00 on Monday already exist; only 10:00 is new
+        assert_eq!(plan.created, 1);
+        assert!(plan.renumbered.is_empty() && plan.removed.is_empty());
+        let added = plan.item.members.last().unwrap();
+        assert_eq!(added.id, ItemId(5));
+        assert_eq!(added.fields["start"], clock("10:00"));
+        assert_eq!(plan.item.members.len(), 5);
+    }
+
+    #[test]
+    fn replacing_renumbers_kept_slots_and_remaps_references() {
+        let mut problem_data = timetable();
+        let mut constraints = vec![constraint(1, json!({ "PreferenceMatrix": {
+            "row_item": "Lecturer", "column_item": "TimeSlot", "default_score": 0,
+            "scores": [{ "row_id": 1, "column_id": 4, "score": 3 }],
+        } }))];
+        let mut schedule = Schedule::new(vec![lesson(1, 2, 1, 4), lesson(2, 2, 1, 2)]);
+
+        let plan = plan_slots(&template(&["Monday", "Tuesday"], "08:00", "10:00", 60), true, &problem_data).unwrap();
+        // Monday 11:00 goes, Tuesday 08:00 moves from 4 to 3, Tuesday 09:00 is new
+        assert_eq!(plan.removed, vec![ItemId(3)]);
+        assert_eq!(plan.renumbered[&ItemId(4)], ItemId(3));
+        assert_eq!(plan.created, 1);
+        let ids: Vec<u32> = plan.item.members.iter().map(|m| m.id.0).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+
+        remap_member_ids("TimeSlot", &plan.renumbered, &mut problem_data, &mut constraints, Some(&mut schedule));
+        let ConstraintRule::PreferenceMatrix { scores, .. } = &constraints[0].rule else {
+            unreachable!();
+        };
+        assert_eq!(scores[0].column_id, ItemId(3));
+        assert_eq!(schedule.assignments[0].resources["TimeSlot"], ItemId(3));
+        assert_eq!(schedule.assignments[1].resources["TimeSlot"], ItemId(2));
+    }
+
+    #[test]
+    fn missing_time_item_is_created() {
+        let mut problem_data = timetable();
+        problem_data.item_categories.remove("TimeSlot");
+
+        let plan = plan_slots(&template(&["Monday"], "08:00", "10:00", 60), false, &problem_data).unwrap();
+        assert_eq!(plan.item.name, "TimeSlot");
+        assert_eq!(plan.item.item_set_type, SetType::E_Set);
+        assert_eq!(plan.created, 2);
+    }
+}
@@ -1,3 +1,4 @@
+use super::item::ItemId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -6,8 +7,9 @@ pub enum ConstraintMode {
     Require,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LogicalOperator {
+    #[default]
     And,
     Or,
 }
@@ -28,18 +30,178 @@ pub enum ComparisonOperator {
     NoOverlap,
 }
 
+/// How two assignments must relate in a `PairwiseRelation` rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairRelation {
+    SameTime,
+    DifferentTime,
+    SameDay,
+    DifferentDay,
+    SameRoom,
+    DifferentRoom,
+    /// On the same day, at least this many minutes between one ending and
+    /// the other starting.
+    MinutesApart(u32),
+    /// The first ends no later than the second starts.
+    Before,
+    /// The first starts no earlier than the second ends.
+    After,
+}
+
+/// Which availability breaches a `ResourceAvailability` rule counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AvailabilityLevel {
+    /// Outside every `Available` window or inside an `Unavailable` one.
+    Hard,
+    /// Inside a `PreferNot` window, and not already a hard breach.
+    PreferNot,
+}
+
+/// Cost of an assignment pairing `row_id` with `column_id`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreferenceScore {
+    pub row_id: ItemId,
+    pub column_id: ItemId,
+    pub score: u32,
+}
+
+/// How a `GlobalBalance` rule measures the spread of the totals.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BalanceMeasure {
+    /// Largest total minus smallest.
+    Range,
+    /// Mean squared difference from the mean total.
+    Variance,
+    /// Sum of absolute differences from the mean total.
+    AbsoluteDeviation,
+}
+
+/// What a `Compactness` rule penalises in each resource's days.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompactnessMeasure {
+    /// Free time slots between the first and last lesson of a day.
+    IdleGaps,
+    /// Days on which the resource has a single lesson.
+    IsolatedLessons,
+    /// Days with lessons beyond `max_days`.
+    TeachingDays { max_days: u32 },
+    /// Lessons beyond `max_consecutive` in a run, where lessons at most
+    /// `max_break_minutes` apart count as one run.
+    ConsecutiveRun {
+        max_consecutive: u32,
+        #[serde(default)]
+        max_break_minutes: u32,
+    },
+}
+
+/// Another field of the same assignment, optionally scaled and shifted:
+/// `field * multiplier + offset`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FieldOperand {
+    pub item_name: String,
+    pub field_key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<f64>,
+    /// Added after the multiplier; minutes when the field is a DateTime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i32>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Condition {
     pub item_name: String,
     pub field_key: String,
     pub operator: ComparisonOperator,
+    #[serde(default)]
     pub target_values: Vec<String>,
+    /// Compares against this field instead of the constant `target_values`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_field: Option<FieldOperand>,
+}
+
+impl Condition {
+    /// `(item_name, field_key)` of every field the condition reads.
+    pub fn field_refs(&self) -> Vec<(&str, &str)> {
+        let mut refs = vec![(self.item_name.as_str(), self.field_key.as_str())];
+        if let Some(operand) = &self.target_field {
+            refs.push((operand.item_name.as_str(), operand.field_key.as_str()));
+        }
+        refs
+    }
+}
+
+/// A boolean expression whose leaves are conditions on one assignment.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ConditionExpr {
+    Condition(Condition),
+    And(Vec<ConditionExpr>),
+    Or(Vec<ConditionExpr>),
+    Not(Box<ConditionExpr>),
+    Implies {
+        antecedent: Box<ConditionExpr>,
+        consequent: Box<ConditionExpr>,
+    },
+}
+
+impl ConditionExpr {
+    pub fn conditions(&self) -> Vec<&Condition> {
+        match self {
+            ConditionExpr::Condition(condition) => vec![condition],
+            ConditionExpr::And(exprs) | ConditionExpr::Or(exprs) => {
+                exprs.iter().flat_map(|e| e.conditions()).collect()
+            }
+            ConditionExpr::Not(expr) => expr.conditions(),
+            ConditionExpr::Implies { antecedent, consequent } => {
+                antecedent.conditions().into_iter().chain(consequent.conditions()).collect()
+            }
+        }
+    }
+
+    pub fn conditions_mut(&mut self) -> Vec<&mut Condition> {
+        match self {
+            ConditionExpr::Condition(condition) => vec![condition],
+            ConditionExpr::And(exprs) | ConditionExpr::Or(exprs) => {
+                exprs.iter_mut().flat_map(|e| e.conditions_mut()).collect()
+            }
+            ConditionExpr::Not(expr) => expr.conditions_mut(),
+            ConditionExpr::Implies { antecedent, consequent } => {
+                antecedent.conditions_mut().into_iter().chain(consequent.conditions_mut()).collect()
+            }
+        }
+    }
+}
+
+/// Conditions as a rule takes them: either a flat list, combined by the
+/// rule's logical operator (AND where the rule has none), or an expression.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConditionSet {
+    List(Vec<Condition>),
+    Expr(ConditionExpr),
+}
+
+impl ConditionSet {
+    pub fn conditions(&self) -> Vec<&Condition> {
+        match self {
+            ConditionSet::List(conditions) => conditions.iter().collect(),
+            ConditionSet::Expr(expr) => expr.conditions(),
+        }
+    }
+
+    pub fn conditions_mut(&mut self) -> Vec<&mut Condition> {
+        match self {
+            ConditionSet::List(conditions) => conditions.iter_mut().collect(),
+            ConditionSet::Expr(expr) => expr.conditions_mut(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ConstraintRule {
     MultiAssignmentCheck {
-        conditions: Vec<Condition>,
+        conditions: ConditionSet,
+        /// Only used when `conditions` is a list.
+        #[serde(default)]
         logical_op: LogicalOperator,
         mode: ConstraintMode,
     },
@@ -47,23 +209,337 @@ pub enum ConstraintRule {
         unique_item_field: String,
         group_item_field: String,
     },
+    /// Keeps how often each value of `target_item_field` appears within
+    /// `min_count..=max_count`, counting shortfalls and excesses alike.
     GlobalCardinality {
         target_item_field: String,
-        max_count: u32,
-        scope_conditions: Option<Vec<Condition>>,
+        #[serde(default)]
+        min_count: Option<u32>,
+        #[serde(default)]
+        max_count: Option<u32>,
+        scope_conditions: Option<ConditionSet>,
+        /// Counts per combination of the target value and the values of these
+        /// fields, e.g. `["TimeSlot:day"]` for "per lecturer per day".
+        #[serde(default)]
+        group_item_fields: Vec<String>,
+        /// Adds up this Integer field (e.g. `Course:duration`) instead of
+        /// counting assignments; the bounds then apply to the sums.
+        #[serde(default)]
+        sum_item_field: Option<String>,
+        /// Also counts target members no assignment uses, so `min_count`
+        /// applies to them; otherwise unused values are left alone.
+        #[serde(default)]
+        include_unused: bool,
     },
     GlobalTemporalPrecedence {
         grouping_item_field: String,
-        first_conditions: Vec<Condition>,
-        second_conditions: Vec<Condition>,
+        first_conditions: ConditionSet,
+        second_conditions: ConditionSet,
         temporal_relation: ComparisonOperator,
         temporal_fields: Vec<String>,
+        /// Item whose `temporal_fields` are compared; either a resource or the
+        /// task item itself. Defaults to the item that represents time.
+        #[serde(default)]
+        temporal_item: Option<String>,
+    },
+    /// Penalises every assignment for which `antecedent` holds but
+    /// `consequent` does not; lists on either side are combined with AND.
+    Implication {
+        antecedent: ConditionSet,
+        consequent: ConditionSet,
+    },
+    /// `relation` must hold between every assignment matching
+    /// `first_conditions` and every other assignment matching
+    /// `second_conditions`.
+    PairwiseRelation {
+        first_conditions: ConditionSet,
+        second_conditions: ConditionSet,
+        relation: PairRelation,
+    },
+    /// Spreads the assignments sharing a value of `group_item_field` over
+    /// distinct values of `spread_item_field`, e.g. `TimeSlot:day`. Without
+    /// `min_distinct` every repeated value counts; with it, only the distinct
+    /// values missing to reach it (capped at the group size).
+    GlobalSpread {
+        group_item_field: String,
+        spread_item_field: String,
+        #[serde(default)]
+        min_distinct: Option<u32>,
+        scope_conditions: Option<ConditionSet>,
+    },
+    /// Penalises every two overlapping assignments of tasks in the same
+    /// curriculum by the curriculum's number of students. Checks the named
+    /// curricula, or all of them when none are named.
+    CurriculumConflict {
+        #[serde(default)]
+        curricula: Vec<String>,
+    },
+    /// Penalises each resource an assignment uses at a time its availability
+    /// windows rule out at `level`. Checks resources of the named items, or
+    /// of all items when none are named.
+    ResourceAvailability {
+        level: AvailabilityLevel,
+        #[serde(default)]
+        item_names: Vec<String>,
+    },
+    /// Adds the score of the (`row_item`, `column_item`) members each
+    /// assignment uses, e.g. Lecturer × TimeSlot, or `default_score` for
+    /// pairs without one. Lower scores are preferred.
+    PreferenceMatrix {
+        row_item: String,
+        column_item: String,
+        #[serde(default)]
+        default_score: u32,
+        #[serde(default)]
+        scores: Vec<PreferenceScore>,
+    },
+    /// Assignments sharing a value of `group_item_field` (e.g. `Course:code`)
+    /// should use at most `max_distinct` values of `resource_item_field`
+    /// (e.g. `Lecturer:id`); each value beyond that counts.
+    GlobalConsistency {
+        group_item_field: String,
+        resource_item_field: String,
+        #[serde(default = "default_max_distinct")]
+        max_distinct: u32,
+        scope_conditions: Option<ConditionSet>,
+    },
+    /// For each value of `resource_item_field` (a lecturer, a student group),
+    /// penalises consecutive assignments on a day whose locations, read from
+    /// `location_item_field` (e.g. `Room:building`), are further apart than
+    /// the break between them.
+    TravelTime {
+        resource_item_field: String,
+        location_item_field: String,
+        scope_conditions: Option<ConditionSet>,
+    },
+    /// Totals, for every member of the item in `target_item_field`, how many
+    /// assignments use it (or the sum of `sum_item_field` over them) and
+    /// costs how unevenly the totals are spread, rounded to a whole number.
+    GlobalBalance {
+        target_item_field: String,
+        #[serde(default)]
+        sum_item_field: Option<String>,
+        measure: BalanceMeasure,
+        scope_conditions: Option<ConditionSet>,
+    },
+    /// Looks at each value of `resource_item_field` (a lecturer, a student
+    /// group, a room) day by day and penalises what `measure` describes.
+    Compactness {
+        resource_item_field: String,
+        scope_conditions: Option<ConditionSet>,
+        measure: CompactnessMeasure,
     },
 }
 
+/// Turns the magnitude of each violation into cost, before the weight.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PenaltyFunction {
+    #[default]
+    Linear,
+    /// One large violation costs more than several small ones.
+    Quadratic,
+    /// `penalty` for every violation larger than `threshold`, nothing for
+    /// the others.
+    Step { threshold: u32, penalty: u32 },
+    /// Each violation costs its magnitude, but no more than `cap`.
+    Capped { cap: u32 },
+}
+
+impl PenaltyFunction {
+    pub fn apply(&self, magnitude: u32) -> u32 {
+        match self {
+            PenaltyFunction::Linear => magnitude,
+            PenaltyFunction::Quadratic => magnitude.saturating_mul(magnitude),
+            PenaltyFunction::Step { threshold, penalty } => if magnitude > *threshold { *penalty } else { 0 },
+            PenaltyFunction::Capped { cap } => magnitude.min(*cap),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct ConstraintId(pub u32);
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Constraint {
+    /// Assigned by the server; whatever a client sends on create is ignored.
+    #[serde(default)]
+    pub id: ConstraintId,
     pub name: String,
     pub weight: u32,
     pub rule: ConstraintRule,
+    /// Disabled constraints are kept but not evaluated.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub penalty: PenaltyFunction,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_distinct() -> u32 {
+    1
+}
+
+impl ConstraintRule {
+    /// Every condition set the rule evaluates, in declaration order.
+    pub fn condition_sets(&self) -> Vec<&ConditionSet> {
+        match self {
+            ConstraintRule::MultiAssignmentCheck { conditions, .. } => vec![conditions],
+            ConstraintRule::GlobalAllDifferent { .. }
+            | ConstraintRule::CurriculumConflict { .. }
+            | ConstraintRule::ResourceAvailability { .. }
+            | ConstraintRule::PreferenceMatrix { .. } => vec![],
+            ConstraintRule::GlobalCardinality { scope_conditions, .. }
+            | ConstraintRule::GlobalSpread { scope_conditions, .. }
+            | ConstraintRule::GlobalConsistency { scope_conditions, .. }
+            | ConstraintRule::TravelTime { scope_conditions, .. }
+            | ConstraintRule::GlobalBalance { scope_conditions, .. }
+            | ConstraintRule::Compactness { scope_conditions, .. } => scope_conditions.iter().collect(),
+            ConstraintRule::GlobalTemporalPrecedence { first_conditions, second_conditions, .. } => {
+                vec![first_conditions, second_conditions]
+            }
+            ConstraintRule::Implication { antecedent, consequent } => vec![antecedent, consequent],
+            ConstraintRule::PairwiseRelation { first_conditions, second_conditions, .. } => {
+                vec![first_conditions, second_conditions]
+            }
+        }
+    }
+
+    pub fn condition_sets_mut(&mut self) -> Vec<&mut ConditionSet> {
+        match self {
+            ConstraintRule::MultiAssignmentCheck { conditions, .. } => vec![conditions],
+            ConstraintRule::GlobalAllDifferent { .. }
+            | ConstraintRule::CurriculumConflict { .. }
+            | ConstraintRule::ResourceAvailability { .. }
+            | ConstraintRule::PreferenceMatrix { .. } => vec![],
+            ConstraintRule::GlobalCardinality { scope_conditions, .. }
+            | ConstraintRule::GlobalSpread { scope_conditions, .. }
+            | ConstraintRule::GlobalConsistency { scope_conditions, .. }
+            | ConstraintRule::TravelTime { scope_conditions, .. }
+            | ConstraintRule::GlobalBalance { scope_conditions, .. }
+            | ConstraintRule::Compactness { scope_conditions, .. } => scope_conditions.iter_mut().collect(),
+            ConstraintRule::GlobalTemporalPrecedence { first_conditions, second_conditions, .. } => {
+                vec![first_conditions, second_conditions]
+            }
+            ConstraintRule::Implication { antecedent, consequent } => vec![antecedent, consequent],
+            ConstraintRule::PairwiseRelation { first_conditions, second_conditions, .. } => {
+                vec![first_conditions, second_conditions]
+            }
+        }
+    }
+
+    /// Every condition the rule evaluates, in declaration order.
+    pub fn conditions(&self) -> Vec<&Condition> {
+        self.condition_sets().into_iter().flat_map(|s| s.conditions()).collect()
+    }
+
+    pub fn conditions_mut(&mut self) -> Vec<&mut Condition> {
+        self.condition_sets_mut().into_iter().flat_map(|s| s.conditions_mut()).collect()
+    }
+
+    /// Items the rule names on their own, outside conditions and
+    /// `Item:field` strings.
+    pub fn item_names(&self) -> Vec<&String> {
+        match self {
+            ConstraintRule::ResourceAvailability { item_names, .. } => item_names.iter().collect(),
+            ConstraintRule::PreferenceMatrix { row_item, column_item, .. } => vec![row_item, column_item],
+            ConstraintRule::GlobalTemporalPrecedence { temporal_item, .. } => temporal_item.iter().collect(),
+            ConstraintRule::MultiAssignmentCheck { .. }
+            | ConstraintRule::GlobalAllDifferent { .. }
+            | ConstraintRule::GlobalCardinality { .. }
+            | ConstraintRule::Implication { .. }
+            | ConstraintRule::PairwiseRelation { .. }
+            | ConstraintRule::GlobalSpread { .. }
+            | ConstraintRule::GlobalConsistency { .. }
+            | ConstraintRule::TravelTime { .. }
+            | ConstraintRule::GlobalBalance { .. }
+            | ConstraintRule::CurriculumConflict { .. }
+            | ConstraintRule::Compactness { .. } => vec![],
+        }
+    }
+
+    /// Every `Item:field` string the rule refers to.
+    pub fn item_field_refs(&self) -> Vec<&String> {
+        match self {
+            ConstraintRule::MultiAssignmentCheck { .. }
+            | ConstraintRule::Implication { .. }
+            | ConstraintRule::PairwiseRelation { .. }
+            | ConstraintRule::CurriculumConflict { .. }
+            | ConstraintRule::ResourceAvailability { .. }
+            | ConstraintRule::PreferenceMatrix { .. } => vec![],
+            ConstraintRule::GlobalAllDifferent { unique_item_field, group_item_field } => {
+                vec![unique_item_field, group_item_field]
+            }
+            ConstraintRule::GlobalSpread { group_item_field, spread_item_field, .. } => {
+                vec![group_item_field, spread_item_field]
+            }
+            ConstraintRule::GlobalConsistency { group_item_field, resource_item_field, .. } => {
+                vec![group_item_field, resource_item_field]
+            }
+            ConstraintRule::TravelTime { resource_item_field, location_item_field, .. } => {
+                vec![resource_item_field, location_item_field]
+            }
+            ConstraintRule::GlobalBalance { target_item_field, sum_item_field, .. } => {
+                std::iter::once(target_item_field).chain(sum_item_field).collect()
+            }
+            ConstraintRule::GlobalCardinality { target_item_field, group_item_fields, sum_item_field, .. } => {
+                std::iter::once(target_item_field).chain(group_item_fields).chain(sum_item_field).collect()
+            }
+            ConstraintRule::GlobalTemporalPrecedence { grouping_item_field, .. } => vec![grouping_item_field],
+            ConstraintRule::Compactness { resource_item_field, .. } => vec![resource_item_field],
+        }
+    }
+
+    pub fn item_field_refs_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            ConstraintRule::MultiAssignmentCheck { .. }
+            | ConstraintRule::Implication { .. }
+            | ConstraintRule::PairwiseRelation { .. }
+            | ConstraintRule::CurriculumConflict { .. }
+            | ConstraintRule::ResourceAvailability { .. }
+            | ConstraintRule::PreferenceMatrix { .. } => vec![],
+            ConstraintRule::GlobalAllDifferent { unique_item_field, group_item_field } => {
+                vec![unique_item_field, group_item_field]
+            }
+            ConstraintRule::GlobalSpread { group_item_field, spread_item_field, .. } => {
+                vec![group_item_field, spread_item_field]
+            }
+            ConstraintRule::GlobalConsistency { group_item_field, resource_item_field, .. } => {
+                vec![group_item_field, resource_item_field]
+            }
+            ConstraintRule::TravelTime { resource_item_field, location_item_field, .. } => {
+                vec![resource_item_field, location_item_field]
+            }
+            ConstraintRule::GlobalBalance { target_item_field, sum_item_field, .. } => {
+                std::iter::once(target_item_field).chain(sum_item_field).collect()
+            }
+            ConstraintRule::GlobalCardinality { target_item_field, group_item_fields, sum_item_field, .. } => {
+                std::iter::once(target_item_field).chain(group_item_fields).chain(sum_item_field).collect()
+            }
+            ConstraintRule::GlobalTemporalPrecedence { grouping_item_field, .. } => vec![grouping_item_field],
+            ConstraintRule::Compactness { resource_item_field, .. } => vec![resource_item_field],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn penalty_functions_shape_magnitudes() {
+        assert_eq!(PenaltyFunction::Linear.apply(7), 7);
+        assert_eq!(PenaltyFunction::Quadratic.apply(7), 49);
+        assert_eq!(PenaltyFunction::Quadratic.apply(u32::MAX), u32::MAX);
+
+        let step = PenaltyFunction::Step { threshold: 3, penalty: 100 };
+        assert_eq!(step.apply(3), 0);
+        assert_eq!(step.apply(4), 100);
+
+        let capped = PenaltyFunction::Capped { cap: 5 };
+        assert_eq!(capped.apply(2), 2);
+        assert_eq!(capped.apply(9), 5);
+    }
 }
@@ -0,0 +1,20 @@
+use super::item::ItemId;
+use serde::{Deserialize, Serialize};
+
+/// Tasks of one item that share students and so must not overlap, e.g. the
+/// courses of a degree programme's semester.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Curriculum {
+    pub name: String,
+    pub task_item: String,
+    pub tasks: Vec<ItemId>,
+    /// Number of students following the curriculum; unset counts as one.
+    #[serde(default)]
+    pub students: Option<u32>,
+}
+
+impl Curriculum {
+    pub fn includes(&self, task_item_name: &str, task_id: ItemId) -> bool {
+        self.task_item == task_item_name && self.tasks.contains(&task_id)
+    }
+}
@@ -0,0 +1,59 @@
+use super::item::ProblemData;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// The `E_Set` item whose members are the periods tasks are scheduled in,
+/// and the fields holding each period's day, start and end.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeDimension {
+    pub item_name: String,
+    #[serde(default = "default_day_field")]
+    pub day_field: String,
+    #[serde(default = "default_start_field")]
+    pub start_field: String,
+    #[serde(default = "default_end_field")]
+    pub end_field: String,
+}
+
+/// The `E_Set` item whose members are the places tasks happen in.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpaceDimension {
+    pub item_name: String,
+}
+
+fn default_day_field() -> String {
+    "day".to_string()
+}
+
+fn default_start_field() -> String {
+    "start".to_string()
+}
+
+fn default_end_field() -> String {
+    "end".to_string()
+}
+
+static DEFAULT_TIME: LazyLock<TimeDimension> = LazyLock::new(|| TimeDimension {
+    item_name: "TimeSlot".to_string(),
+    day_field: default_day_field(),
+    start_field: default_start_field(),
+    end_field: default_end_field(),
+});
+
+static DEFAULT_SPACE: LazyLock<SpaceDimension> = LazyLock::new(|| SpaceDimension {
+    item_name: "Room".to_string(),
+});
+
+impl ProblemData {
+    /// The designated time dimension, or a `TimeSlot` item with `day`,
+    /// `start` and `end` fields when none is designated.
+    pub fn time(&self) -> &TimeDimension {
+        self.time_dimension.as_ref().unwrap_or(&DEFAULT_TIME)
+    }
+
+    /// The designated space dimension, or a `Room` item when none is
+    /// designated.
+    pub fn space(&self) -> &SpaceDimension {
+        self.space_dimension.as_ref().unwrap_or(&DEFAULT_SPACE)
+    }
+}
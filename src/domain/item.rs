@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct ItemId(pub u32);
 
+#[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum SetType {
     B_Set,
@@ -36,10 +37,37 @@ pub struct Schema {
     pub definitions: HashMap<String, FieldSchema>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AvailabilityKind {
+    /// Once a member has any of these, it is only available inside them.
+    Available,
+    Unavailable,
+    PreferNot,
+}
+
+/// A period in which a resource is, or is not, available. A window only
+/// applies on its weekday or date when set; without `start`/`end` it covers
+/// the whole day.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AvailabilityWindow {
+    pub kind: AvailabilityKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekday: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Member {
     pub id: ItemId,
     pub fields: HashMap<String, Value>,
+    /// Only used for members of `E_Set` items.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub availability: Vec<AvailabilityWindow>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -48,9 +76,92 @@ pub struct Item {
     pub item_set_type: SetType,
     pub members: Vec<Member>,
     pub schema: Schema,
+    /// Highest member ID handed out so far, including members deleted since,
+    /// so that their IDs are not given out again.
+    #[serde(default)]
+    pub last_member_id: u32,
+}
+
+impl Item {
+    pub fn member(&self, id: ItemId) -> Option<&Member> {
+        self.members.iter().find(|m| m.id == id)
+    }
+
+    /// Hands out an ID greater than every ID in use or handed out before, or
+    /// `None` once the IDs are used up.
+    pub fn allocate_member_id(&mut self) -> Option<ItemId> {
+        let last = self.members.iter().map(|m| m.id.0).fold(self.last_member_id, u32::max);
+        let id = last.checked_add(1)?;
+        self.last_member_id = id;
+        Some(ItemId(id))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProblemData {
     pub item_categories: HashMap<String, Item>,
+    #[serde(default)]
+    pub curricula: Vec<super::curriculum::Curriculum>,
+    #[serde(default)]
+    pub pairings: Vec<super::pairing::ResourcePairing>,
+    #[serde(default)]
+    pub location_distances: Vec<super::location::LocationDistance>,
+    #[serde(default)]
+    pub time_dimension: Option<super::dimension::TimeDimension>,
+    #[serde(default)]
+    pub space_dimension: Option<super::dimension::SpaceDimension>,
+}
+
+impl Value {
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            Value::String(_) => FieldType::Text,
+            Value::Number(_) => FieldType::Integer,
+            Value::Date(_) => FieldType::DateTime,
+        }
+    }
+
+    /// Converts the value to `field_type`, or `None` when it has no sensible
+    /// representation there (e.g. `"B11"` as an Integer).
+    pub fn convert_to(&self, field_type: &FieldType) -> Option<Value> {
+        match (self, field_type) {
+            (Value::String(s), FieldType::Text) | (Value::Date(s), FieldType::Text) => {
+                Some(Value::String(s.clone()))
+            }
+            (Value::Number(n), FieldType::Text) => Some(Value::String(n.to_string())),
+            (Value::Number(n), FieldType::Integer) => Some(Value::Number(*n)),
+            (Value::String(s), FieldType::Integer) => s.trim().parse().ok().map(Value::Number),
+            (Value::String(s), FieldType::DateTime) | (Value::Date(s), FieldType::DateTime) => {
+                Some(Value::Date(s.clone()))
+            }
+            (Value::Date(_), FieldType::Integer) | (Value::Number(_), FieldType::DateTime) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    #[test]
+    fn allocated_ids_are_not_reused_after_deletes() {
+        let mut rooms = item("Room", SetType::E_Set, &[], vec![member(1, &[]), member(4, &[])]);
+        assert_eq!(rooms.allocate_member_id(), Some(ItemId(5)));
+        rooms.members.push(member(5, &[]));
+        rooms.members.retain(|m| m.id != ItemId(5) && m.id != ItemId(4));
+        assert_eq!(rooms.allocate_member_id(), Some(ItemId(6)));
+        assert_eq!(rooms.allocate_member_id(), Some(ItemId(7)));
+    }
+
+    #[test]
+    fn allocation_stops_when_ids_run_out() {
+        let mut rooms = item("Room", SetType::E_Set, &[], vec![member(u32::MAX - 1, &[])]);
+        assert_eq!(rooms.allocate_member_id(), Some(ItemId(u32::MAX)));
+        assert_eq!(rooms.allocate_member_id(), None);
+
+        let mut rooms = item("Room", SetType::E_Set, &[], vec![member(u32::MAX, &[])]);
+        assert_eq!(rooms.allocate_member_id(), None);
+        assert_eq!(rooms.last_member_id, 0);
+    }
 }
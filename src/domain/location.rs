@@ -0,0 +1,24 @@
+use super::item::ProblemData;
+use serde::{Deserialize, Serialize};
+
+/// Minutes it takes to get from one location to another, e.g. between two
+/// buildings. Distances apply in both directions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocationDistance {
+    pub from: String,
+    pub to: String,
+    pub minutes: u32,
+}
+
+impl ProblemData {
+    /// Travel time between two locations; zero within one location or when
+    /// no distance is known.
+    pub fn travel_minutes(&self, from: &str, to: &str) -> u32 {
+        if from == to {
+            return 0;
+        }
+        self.location_distances.iter()
+            .find(|d| (d.from == from && d.to == to) || (d.from == to && d.to == from))
+            .map_or(0, |d| d.minutes)
+    }
+}
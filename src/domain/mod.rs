@@ -1,7 +1,16 @@
 pub mod item;
 pub mod schedule;
 pub mod constraint;
+pub mod curriculum;
+pub mod pairing;
+pub mod location;
+pub mod dimension;
+pub mod time;
 
 pub use item::*;
 pub use schedule::*;
 pub use constraint::*;
+pub use curriculum::*;
+pub use pairing::*;
+pub use location::*;
+pub use dimension::*;
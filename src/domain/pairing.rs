@@ -0,0 +1,65 @@
+use super::item::{ItemId, Member, ProblemData, Value};
+use serde::{Deserialize, Serialize};
+
+/// Limits which members of `resource_item` may serve the tasks of
+/// `task_item`, e.g. which lecturers can teach which courses.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourcePairing {
+    pub task_item: String,
+    pub resource_item: String,
+    pub rule: PairingRule,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum PairingRule {
+    /// Tasks listed may only use the resources listed with them; a single
+    /// resource fixes the pairing. Tasks not listed may use any member.
+    Table { allowed: Vec<AllowedResources> },
+    /// The task's `task_field` must equal the resource's `resource_field`.
+    FieldMatch { task_field: String, resource_field: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AllowedResources {
+    pub task_id: ItemId,
+    pub resource_ids: Vec<ItemId>,
+}
+
+impl ProblemData {
+    /// Members of `resource_item` that every pairing lets the task use, in
+    /// member order. Without pairings that is every member.
+    pub fn allowed_resources(&self, task_item: &str, task_id: ItemId, resource_item: &str) -> Vec<ItemId> {
+        let Some(resources) = self.item_categories.get(resource_item) else {
+            return Vec::new();
+        };
+        let task = self.item_categories.get(task_item).and_then(|item| item.member(task_id));
+        let pairings: Vec<&ResourcePairing> = self.pairings.iter()
+            .filter(|p| p.task_item == task_item && p.resource_item == resource_item)
+            .collect();
+
+        resources.members.iter()
+            .filter(|resource| pairings.iter().all(|pairing| match &pairing.rule {
+                PairingRule::Table { allowed } => allowed.iter()
+                    .find(|a| a.task_id == task_id)
+                    .is_none_or(|a| a.resource_ids.contains(&resource.id)),
+                PairingRule::FieldMatch { task_field, resource_field } => {
+                    let task_value = task.and_then(|t| field(t, task_field));
+                    task_value.is_some() && task_value == field(resource, resource_field)
+                }
+            }))
+            .map(|resource| resource.id)
+            .collect()
+    }
+
+    /// Whether every pairing lets the task use `resource_id`.
+    pub fn is_allowed_resource(&self, task_item: &str, task_id: ItemId, resource_item: &str, resource_id: ItemId) -> bool {
+        self.allowed_resources(task_item, task_id, resource_item).contains(&resource_id)
+    }
+}
+
+fn field(member: &Member, field_key: &str) -> Option<Value> {
+    if field_key == "id" {
+        return Some(Value::Number(member.id.0 as i32));
+    }
+    member.fields.get(field_key).cloned()
+}
@@ -0,0 +1,152 @@
+//! Parsing for the textual times and dates stored in `Value::Date`.
+
+/// Minutes since midnight of `"HH:MM"`, `"HH:MM:SS"`, or the time part of an
+/// ISO date-time such as `"2025-10-06T08:00"`.
+pub fn parse_clock(value: &str) -> Option<u32> {
+    let time = match value.split_once(['T', ' ']) {
+        Some((date, time)) => {
+            parse_date(date)?;
+            time
+        }
+        None => value,
+    };
+
+    let mut parts = time.split(':');
+    let hours: u32 = parse_digits(parts.next()?, 1..=2)?;
+    let minutes: u32 = parse_digits(parts.next()?, 2..=2)?;
+    if let Some(seconds) = parts.next() {
+        if parse_digits::<u32>(seconds, 2..=2)? > 59 {
+            return None;
+        }
+    }
+    if parts.next().is_some() || hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    Some(hours * 60 + minutes)
+}
+
+/// `(year, month, day)` of `"YYYY-MM-DD"` or the date part of an ISO date-time.
+pub fn parse_date(value: &str) -> Option<(i32, u32, u32)> {
+    let date = value.split_once(['T', ' ']).map_or(value, |(date, _)| date);
+    let mut parts = date.split('-');
+    let year = parse_digits(parts.next()?, 4..=4)?;
+    let month = parse_digits(parts.next()?, 2..=2)?;
+    let day = parse_digits(parts.next()?, 2..=2)?;
+    if parts.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some((year, month, day))
+}
+
+/// Minutes since midnight of a time, or since 1970-01-01 of a date or
+/// date-time, and whether `value` had a date; values compare in order only
+/// when both had a date or neither did.
+pub fn parse_minutes(value: &str) -> Option<(bool, i64)> {
+    if let Some(minutes) = parse_clock(value) {
+        let days = match value.contains(['T', ' ']) {
+            true => days_since_epoch(parse_date(value)?),
+            false => return Some((false, minutes as i64)),
+        };
+        return Some((true, days * 24 * 60 + minutes as i64));
+    }
+    let date = parse_date(value).filter(|_| !value.contains(['T', ' ']))?;
+    Some((true, days_since_epoch(date) * 24 * 60))
+}
+
+/// Days from 1970-01-01 to the given date of the proleptic Gregorian calendar.
+fn days_since_epoch((year, month, day): (i32, u32, u32)) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year } as i64;
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Whether `value` is a time, a date, or a date-time in one of the forms above.
+pub fn is_valid_datetime(value: &str) -> bool {
+    parse_clock(value).is_some() || (!value.contains(['T', ' ']) && parse_date(value).is_some())
+}
+
+const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// Position of an English weekday name in the week, Monday being 0.
+pub fn weekday_index(day: &str) -> Option<u32> {
+    let day = day.trim();
+    WEEKDAYS.iter().position(|d| d.eq_ignore_ascii_case(day)).map(|i| i as u32)
+}
+
+/// English name of the weekday at `index`, Monday being 0.
+pub fn weekday_name(index: u32) -> Option<&'static str> {
+    WEEKDAYS.get(index as usize).copied()
+}
+
+/// `"HH:MM"` of minutes since midnight.
+pub fn format_clock(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Weekday of an English weekday name or of an ISO date, Monday being 0.
+pub fn weekday_of(day: &str) -> Option<u32> {
+    if let Some(index) = weekday_index(day) {
+        return Some(index);
+    }
+    // Sakamoto's method, shifted so that Monday is 0
+    let (year, month, day) = parse_date(day)?;
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    let year = if month < 3 { year - 1 } else { year };
+    let sunday_based = (year + year / 4 - year / 100 + year / 400 + OFFSETS[month as usize - 1] + day as i32).rem_euclid(7);
+    Some(((sunday_based + 6) % 7) as u32)
+}
+
+fn parse_digits<T: std::str::FromStr>(part: &str, len: std::ops::RangeInclusive<usize>) -> Option<T> {
+    if len.contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit()) {
+        part.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minutes_order_times_and_dates_separately() {
+        assert_eq!(parse_minutes("9:05"), Some((false, 545)));
+        assert_eq!(parse_minutes("1970-01-02"), Some((true, 1440)));
+        assert_eq!(parse_minutes("1970-01-01T01:30"), Some((true, 90)));
+        assert_eq!(parse_minutes("1969-12-31 23:00"), Some((true, -60)));
+        assert_eq!(parse_minutes("2000-03-01").unwrap().1 - parse_minutes("2000-02-28").unwrap().1, 2 * 1440);
+        assert_eq!(parse_minutes("2100-03-01").unwrap().1 - parse_minutes("2100-02-28").unwrap().1, 1440);
+        assert_eq!(parse_minutes("noon"), None);
+        assert_eq!(parse_minutes("2025-13-01"), None);
+    }
+
+    #[test]
+    fn clocks_and_dates_are_parsed_strictly() {
+        assert_eq!(parse_clock("8:30"), Some(510));
+        assert_eq!(parse_clock("23:59:59"), Some(1439));
+        assert_eq!(parse_clock("2025-10-06T08:00"), Some(480));
+        assert_eq!(parse_clock("24:00"), None);
+        assert_eq!(parse_clock("8:5"), None);
+        assert_eq!(parse_clock("2025-10-6T08:00"), None);
+        assert_eq!(parse_date("2025-10-06"), Some((2025, 10, 6)));
+        assert_eq!(parse_date("2025-10-06T08:00"), Some((2025, 10, 6)));
+        assert_eq!(parse_date("25-10-06"), None);
+
+        assert!(is_valid_datetime("08:00") && is_valid_datetime("2025-10-06") && is_valid_datetime("2025-10-06 08:00"));
+        assert!(!is_valid_datetime("2025-10-06T") && !is_valid_datetime("Monday"));
+    }
+
+    #[test]
+    fn weekdays_come_from_names_or_dates() {
+        assert_eq!(weekday_of(" friday "), Some(4));
+        assert_eq!(weekday_of("2025-10-06"), Some(0));
+        assert_eq!(weekday_of("2024-02-29"), Some(3));
+        assert_eq!(weekday_of("2000-01-02T10:00"), Some(6));
+        assert_eq!(weekday_of("Funday"), None);
+    }
+}
@@ -6,6 +6,8 @@ pub mod application;
 pub mod app_state;
 pub mod api_error;
 pub mod routes;
+#[cfg(test)]
+mod test_support;
 
 use app_state::AppState;
 use routes::*;
@@ -26,6 +28,7 @@ impl Application {
         .route("/items", post(create_item))
         .route("/items", get(list_items))
         .route("/items/{item_name}", put(update_item).delete(delete_item))
+        .route("/items/{item_name}/migrations", post(migrate_item_schema))
         // members
         .route("/items/{item_name}/members", post(add_member))
         .route("/items/{item_name}/members", get(list_members))
@@ -33,9 +36,24 @@ impl Application {
         // constraints
         .route("/constraints", post(create_constraint))
         .route("/constraints", get(list_constraints))
-        .route("/constraints/{name}", put(update_constraint).delete(delete_constraint))
+        .route("/constraints/{id}", put(update_constraint).delete(delete_constraint))
+        .route("/constraints/{id}/enabled", put(set_constraint_enabled))
+        .route("/constraints/{id}/preferences", put(upload_preferences))
+        // curricula
+        .route("/curricula", post(create_curriculum).get(list_curricula))
+        .route("/curricula/{name}", put(update_curriculum).delete(delete_curriculum))
+        // pairings
+        .route("/pairings", get(list_pairings))
+        .route("/pairings/{task_item}/{resource_item}", put(set_pairing).delete(delete_pairing))
+        // locations
+        .route("/locations/distances", get(list_distances).put(set_distances))
+        // dimensions
+        .route("/dimensions", get(get_dimensions).put(set_dimensions))
+        .route("/timeslots/generate", post(generate_timeslots))
         // solver
         .route("/solve", post(solve))
+        // integrity
+        .route("/integrity", get(check_integrity))
             .layer(cors)
             .with_state(app_state);
 
@@ -1,16 +1,12 @@
-use std::{collections::HashMap, sync::Arc};
+use std::collections::HashMap;
 use scheduling_optimizer::{app_state::AppState, domain::*};
-use tokio::sync::RwLock;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let problem_data = create_sample_problem();
     let constraints = create_sample_constraints();
 
-    let app_state = AppState::new(
-        Arc::new(RwLock::new(problem_data)),
-        Arc::new(RwLock::new(constraints)),
-    );
+    let app_state = AppState::new(problem_data, constraints, None);
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
     let addr = format!("0.0.0.0:{}", port);
@@ -44,6 +40,14 @@ fn create_sample_problem() -> ProblemData {
                     is_required: true,
                 },
             ),
+            (
+                "enrollment".to_string(),
+                FieldSchema {
+                    field_name: "enrollment".to_string(),
+                    field_type: FieldType::Integer,
+                    is_required: true,
+                },
+            ),
         ]),
     };
 
@@ -56,7 +60,9 @@ fn create_sample_problem() -> ProblemData {
                     Value::String("Photogrammetric CV".to_string()),
                 ),
                 ("duration".to_string(), Value::Number(90)),
+                ("enrollment".to_string(), Value::Number(45)),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(2),
@@ -66,7 +72,9 @@ fn create_sample_problem() -> ProblemData {
                     Value::String("Machine Learning".to_string()),
                 ),
                 ("duration".to_string(), Value::Number(90)),
+                ("enrollment".to_string(), Value::Number(60)),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(3),
@@ -76,14 +84,18 @@ fn create_sample_problem() -> ProblemData {
                     Value::String("Virtual Reality".to_string()),
                 ),
                 ("duration".to_string(), Value::Number(90)),
+                ("enrollment".to_string(), Value::Number(28)),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(4),
             fields: HashMap::from([
                 ("name".to_string(), Value::String("HCI Theory".to_string())),
                 ("duration".to_string(), Value::Number(90)),
+                ("enrollment".to_string(), Value::Number(20)),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(5),
@@ -93,7 +105,9 @@ fn create_sample_problem() -> ProblemData {
                     Value::String("Computer Vision".to_string()),
                 ),
                 ("duration".to_string(), Value::Number(90)),
+                ("enrollment".to_string(), Value::Number(35)),
             ]),
+            availability: vec![],
         },
     ];
 
@@ -104,6 +118,7 @@ fn create_sample_problem() -> ProblemData {
             item_set_type: SetType::B_Set,
             members: courses,
             schema: course_schema,
+            last_member_id: 0,
         },
     );
 
@@ -126,6 +141,14 @@ fn create_sample_problem() -> ProblemData {
                     is_required: true,
                 },
             ),
+            (
+                "building".to_string(),
+                FieldSchema {
+                    field_name: "building".to_string(),
+                    field_type: FieldType::Text,
+                    is_required: false,
+                },
+            ),
         ]),
     };
 
@@ -135,28 +158,36 @@ fn create_sample_problem() -> ProblemData {
             fields: HashMap::from([
                 ("name".to_string(), Value::String("B11".to_string())),
                 ("capacity".to_string(), Value::Number(50)),
+                ("building".to_string(), Value::String("Main Building".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(2),
             fields: HashMap::from([
                 ("name".to_string(), Value::String("SR_A".to_string())),
                 ("capacity".to_string(), Value::Number(30)),
+                ("building".to_string(), Value::String("Main Building".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(3),
             fields: HashMap::from([
                 ("name".to_string(), Value::String("SR_H".to_string())),
                 ("capacity".to_string(), Value::Number(25)),
+                ("building".to_string(), Value::String("Digital Bauhaus Lab".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(4),
             fields: HashMap::from([
                 ("name".to_string(), Value::String("LH_HK7".to_string())),
                 ("capacity".to_string(), Value::Number(100)),
+                ("building".to_string(), Value::String("Audimax".to_string())),
             ]),
+            availability: vec![],
         },
     ];
 
@@ -197,6 +228,7 @@ fn create_sample_problem() -> ProblemData {
             item_set_type: SetType::E_Set,
             members: rooms,
             schema: room_schema,
+            last_member_id: 0,
         },
     );
 
@@ -209,6 +241,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("08:00".to_string())),
                 ("end".to_string(), Value::Date("09:30".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(2),
@@ -217,6 +250,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("09:45".to_string())),
                 ("end".to_string(), Value::Date("11:15".to_string())),
             ]),
+            availability: vec![],
         },
         // 12:00-13:30 is LUNCH (no slot)
         Member {
@@ -226,6 +260,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("13:30".to_string())),
                 ("end".to_string(), Value::Date("15:00".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(4),
@@ -234,6 +269,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("09:45".to_string())),
                 ("end".to_string(), Value::Date("11:15".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(5),
@@ -242,6 +278,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("13:30".to_string())),
                 ("end".to_string(), Value::Date("15:00".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(6),
@@ -250,6 +287,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("09:45".to_string())),
                 ("end".to_string(), Value::Date("11:15".to_string())),
             ]),
+            availability: vec![],
         },
         Member {
             id: ItemId(7),
@@ -258,6 +296,7 @@ fn create_sample_problem() -> ProblemData {
                 ("start".to_string(), Value::Date("15:15".to_string())),
                 ("end".to_string(), Value::Date("16:45".to_string())),
             ]),
+            availability: vec![],
         },
     ];
 
@@ -269,6 +308,7 @@ fn create_sample_problem() -> ProblemData {
             item_set_type: SetType::E_Set,
             members: time_slots,
             schema: time_schema,
+            last_member_id: 0,
         },
     );
 
@@ -291,10 +331,27 @@ fn create_sample_problem() -> ProblemData {
                 "name".to_string(),
                 Value::String("Prof. Rodehorst".to_string()),
             )]),
+            availability: vec![],
         },
         Member {
             id: ItemId(2),
             fields: HashMap::from([("name".to_string(), Value::String("Prof. Stein".to_string()))]),
+            availability: vec![
+                AvailabilityWindow {
+                    kind: AvailabilityKind::Unavailable,
+                    weekday: Some("Friday".to_string()),
+                    date: None,
+                    start: None,
+                    end: None,
+                },
+                AvailabilityWindow {
+                    kind: AvailabilityKind::PreferNot,
+                    weekday: Some("Monday".to_string()),
+                    date: None,
+                    start: None,
+                    end: Some("10:00".to_string()),
+                },
+            ],
         },
         Member {
             id: ItemId(3),
@@ -302,6 +359,7 @@ fn create_sample_problem() -> ProblemData {
                 "name".to_string(),
                 Value::String("Prof. Fröhlich".to_string()),
             )]),
+            availability: vec![],
         },
         Member {
             id: ItemId(4),
@@ -309,6 +367,7 @@ fn create_sample_problem() -> ProblemData {
                 "name".to_string(),
                 Value::String("Prof. Hornecker".to_string()),
             )]),
+            availability: vec![],
         },
     ];
 
@@ -319,10 +378,57 @@ fn create_sample_problem() -> ProblemData {
             item_set_type: SetType::E_Set,
             members: lecturers,
             schema: lecturer_schema,
+            last_member_id: 0,
         },
     );
 
-    ProblemData { item_categories }
+    // Curricula: courses taken together by the same students
+    let curricula = vec![
+        Curriculum {
+            name: "Computer Vision Track".to_string(),
+            task_item: "Course".to_string(),
+            tasks: vec![ItemId(1), ItemId(2), ItemId(5)],
+            students: Some(25),
+        },
+        Curriculum {
+            name: "Human-Computer Interaction Track".to_string(),
+            task_item: "Course".to_string(),
+            tasks: vec![ItemId(3), ItemId(4)],
+            students: Some(15),
+        },
+    ];
+
+    // Lecturers only teach their own courses; Computer Vision is shared
+    let pairings = vec![ResourcePairing {
+        task_item: "Course".to_string(),
+        resource_item: "Lecturer".to_string(),
+        rule: PairingRule::Table {
+            allowed: vec![
+                AllowedResources { task_id: ItemId(1), resource_ids: vec![ItemId(1)] },
+                AllowedResources { task_id: ItemId(2), resource_ids: vec![ItemId(2)] },
+                AllowedResources { task_id: ItemId(3), resource_ids: vec![ItemId(3)] },
+                AllowedResources { task_id: ItemId(4), resource_ids: vec![ItemId(4)] },
+                AllowedResources { task_id: ItemId(5), resource_ids: vec![ItemId(1), ItemId(2)] },
+            ],
+        },
+    }];
+
+    // Walking times between buildings
+    let location_distances = vec![
+        LocationDistance { from: "Main Building".to_string(), to: "Digital Bauhaus Lab".to_string(), minutes: 15 },
+        LocationDistance { from: "Main Building".to_string(), to: "Audimax".to_string(), minutes: 5 },
+        LocationDistance { from: "Digital Bauhaus Lab".to_string(), to: "Audimax".to_string(), minutes: 15 },
+    ];
+
+    let time_dimension = Some(TimeDimension {
+        item_name: "TimeSlot".to_string(),
+        day_field: "day".to_string(),
+        start_field: "start".to_string(),
+        end_field: "end".to_string(),
+    });
+    let space_dimension = Some(SpaceDimension { item_name: "Room".to_string() });
+
+    ProblemData { item_categories, curricula, pairings, location_distances, time_dimension, space_dimension }
 }
 
 fn create_sample_constraints() -> Vec<Constraint> {
@@ -331,27 +437,34 @@ fn create_sample_constraints() -> Vec<Constraint> {
 
         // No double-booking
         Constraint {
+            id: ConstraintId(1),
             name: "No Room Conflicts".to_string(),
             weight: 100,
             rule: ConstraintRule::GlobalAllDifferent {
                 unique_item_field: "Room:id".to_string(),
                 group_item_field: "TimeSlot:id".to_string(),
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         Constraint {
+            id: ConstraintId(2),
             name: "No Lecturer Conflicts".to_string(),
             weight: 100,
             rule: ConstraintRule::GlobalAllDifferent {
                 unique_item_field: "Lecturer:id".to_string(),
                 group_item_field: "TimeSlot:id".to_string(),
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         // Lunch break (12:00-13:30 protected)
         Constraint {
+            id: ConstraintId(3),
             name: "Mandatory Lunch Break".to_string(),
             weight: 150,
             rule: ConstraintRule::MultiAssignmentCheck {
-                conditions: vec![Condition {
+                conditions: ConditionSet::List(vec![Condition {
                     item_name: "TimeSlot".to_string(),
                     field_key: "start".to_string(),
                     operator: ComparisonOperator::In,
@@ -360,106 +473,306 @@ fn create_sample_constraints() -> Vec<Constraint> {
                         "12:30".to_string(),
                         "13:00".to_string(),
                     ],
-                }],
+                    target_field: None,
+                }]),
                 logical_op: LogicalOperator::Or,
                 mode: ConstraintMode::Forbid,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
-        // Room capacity (assuming courses have enrollment field)
+        // Room capacity must cover the course's enrollment
         Constraint {
+            id: ConstraintId(4),
             name: "Room Must Fit Students".to_string(),
             weight: 100,
             rule: ConstraintRule::MultiAssignmentCheck {
-                conditions: vec![Condition {
+                conditions: ConditionSet::List(vec![Condition {
                     item_name: "Room".to_string(),
                     field_key: "capacity".to_string(),
                     operator: ComparisonOperator::LessThan,
-                    target_values: vec!["40".to_string()],
-                }],
+                    target_values: vec![],
+                    target_field: Some(FieldOperand {
+                        item_name: "Course".to_string(),
+                        field_key: "enrollment".to_string(),
+                        multiplier: None,
+                        offset: None,
+                    }),
+                }]),
                 logical_op: LogicalOperator::And,
                 mode: ConstraintMode::Forbid,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         // === SOFT CONSTRAINTS ===
 
         // Typical lecturer load: 1-2 courses per semester (STRICT)
         Constraint {
+            id: ConstraintId(5),
             name: "Max 2 Courses Per Lecturer".to_string(),
             weight: 80,
             rule: ConstraintRule::GlobalCardinality {
                 target_item_field: "Lecturer:id".to_string(),
-                max_count: 2,
+                min_count: None,
+                max_count: Some(2),
                 scope_conditions: None,
+                group_item_fields: vec![],
+                sum_item_field: None,
+                include_unused: false,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         // Prefer compact schedule - avoid late evenings (after 18:00)
         Constraint {
+            id: ConstraintId(6),
             name: "No Late Evening Classes".to_string(),
             weight: 60,
             rule: ConstraintRule::MultiAssignmentCheck {
-                conditions: vec![Condition {
+                conditions: ConditionSet::List(vec![Condition {
                     item_name: "TimeSlot".to_string(),
                     field_key: "start".to_string(),
                     operator: ComparisonOperator::GreaterThanOrEqual,
                     target_values: vec!["18:00".to_string()],
-                }],
+                    target_field: None,
+                }]),
                 logical_op: LogicalOperator::And,
                 mode: ConstraintMode::Forbid,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         // Friday afternoon soft preference
         Constraint {
+            id: ConstraintId(7),
             name: "Avoid Friday Afternoon".to_string(),
             weight: 25,
             rule: ConstraintRule::MultiAssignmentCheck {
-                conditions: vec![
+                conditions: ConditionSet::List(vec![
                     Condition {
                         item_name: "TimeSlot".to_string(),
                         field_key: "day".to_string(),
                         operator: ComparisonOperator::Equal,
                         target_values: vec!["Friday".to_string()],
+                        target_field: None,
                     },
                     Condition {
                         item_name: "TimeSlot".to_string(),
                         field_key: "start".to_string(),
                         operator: ComparisonOperator::GreaterThanOrEqual,
                         target_values: vec!["13:00".to_string()],
+                        target_field: None,
                     },
-                ],
+                ]),
                 logical_op: LogicalOperator::And,
                 mode: ConstraintMode::Forbid,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         // Prefer morning slots for lectures (before 13:00)
         Constraint {
+            id: ConstraintId(8),
             name: "Prefer Morning Teaching".to_string(),
             weight: 15,
             rule: ConstraintRule::MultiAssignmentCheck {
-                conditions: vec![Condition {
+                conditions: ConditionSet::List(vec![Condition {
                     item_name: "TimeSlot".to_string(),
                     field_key: "start".to_string(),
                     operator: ComparisonOperator::GreaterThanOrEqual,
                     target_values: vec!["15:00".to_string()],
-                }],
+                    target_field: None,
+                }]),
                 logical_op: LogicalOperator::And,
                 mode: ConstraintMode::Forbid,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
         // Full-time professors need one free afternoon for research/admin
         // (This would need professor type field - example shown)
         Constraint {
+            id: ConstraintId(9),
             name: "Professor Research Time".to_string(),
             weight: 40,
             rule: ConstraintRule::GlobalCardinality {
                 target_item_field: "Lecturer:id".to_string(),
-                max_count: 3, // Max 3 afternoon slots per lecturer
-                scope_conditions: Some(vec![Condition {
+                min_count: None,
+                max_count: Some(3), // Max 3 afternoon slots per lecturer
+                scope_conditions: Some(ConditionSet::List(vec![Condition {
                     item_name: "TimeSlot".to_string(),
                     field_key: "start".to_string(),
                     operator: ComparisonOperator::GreaterThanOrEqual,
                     target_values: vec!["13:30".to_string()],
+                    target_field: None,
+                }])),
+                group_item_fields: vec![],
+                sum_item_field: None,
+                include_unused: false,
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Prof. Fröhlich does not teach on Wednesdays
+        Constraint {
+            id: ConstraintId(10),
+            name: "Fröhlich Not On Wednesday".to_string(),
+            weight: 30,
+            rule: ConstraintRule::Implication {
+                antecedent: ConditionSet::List(vec![Condition {
+                    item_name: "Lecturer".to_string(),
+                    field_key: "name".to_string(),
+                    operator: ComparisonOperator::Equal,
+                    target_values: vec!["Prof. Fröhlich".to_string()],
+                    target_field: None,
                 }]),
+                consequent: ConditionSet::List(vec![Condition {
+                    item_name: "TimeSlot".to_string(),
+                    field_key: "day".to_string(),
+                    operator: ComparisonOperator::NotEqual,
+                    target_values: vec!["Wednesday".to_string()],
+                    target_field: None,
+                }]),
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Daily teaching load: at most 4 hours per lecturer per day
+        Constraint {
+            id: ConstraintId(11),
+            name: "Max 4 Teaching Hours Per Lecturer Per Day".to_string(),
+            weight: 80,
+            rule: ConstraintRule::GlobalCardinality {
+                target_item_field: "Lecturer:id".to_string(),
+                min_count: None,
+                max_count: Some(240),
+                scope_conditions: None,
+                group_item_fields: vec!["TimeSlot:day".to_string()],
+                sum_item_field: Some("Course:duration".to_string()),
+                include_unused: false,
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Compact lecturer days: no idle slots between classes
+        Constraint {
+            id: ConstraintId(12),
+            name: "No Idle Gaps For Lecturers".to_string(),
+            weight: 15,
+            rule: ConstraintRule::Compactness {
+                resource_item_field: "Lecturer:id".to_string(),
+                scope_conditions: None,
+                measure: CompactnessMeasure::IdleGaps,
+            },
+            enabled: true,
+            // A day with one long gap is worse than two days with a short one
+            penalty: PenaltyFunction::Quadratic,
+        },
+        // Spread each lecturer's courses over the week
+        Constraint {
+            id: ConstraintId(13),
+            name: "Spread Lecturer Courses Over Days".to_string(),
+            weight: 10,
+            rule: ConstraintRule::GlobalSpread {
+                group_item_field: "Lecturer:id".to_string(),
+                spread_item_field: "TimeSlot:day".to_string(),
+                min_distinct: None,
+                scope_conditions: None,
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Students of a curriculum must be able to attend all its courses
+        Constraint {
+            id: ConstraintId(14),
+            name: "No Curriculum Overlaps".to_string(),
+            weight: 10,
+            rule: ConstraintRule::CurriculumConflict { curricula: vec![] },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Lecturer and room calendars
+        Constraint {
+            id: ConstraintId(15),
+            name: "Respect Resource Availability".to_string(),
+            weight: 100,
+            rule: ConstraintRule::ResourceAvailability {
+                level: AvailabilityLevel::Hard,
+                item_names: vec![],
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        Constraint {
+            id: ConstraintId(16),
+            name: "Avoid Prefer-Not Times".to_string(),
+            weight: 20,
+            rule: ConstraintRule::ResourceAvailability {
+                level: AvailabilityLevel::PreferNot,
+                item_names: vec![],
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Graded time preferences of Prof. Rodehorst (0 = preferred)
+        Constraint {
+            id: ConstraintId(17),
+            name: "Rodehorst Time Preferences".to_string(),
+            weight: 5,
+            rule: ConstraintRule::PreferenceMatrix {
+                row_item: "Lecturer".to_string(),
+                column_item: "TimeSlot".to_string(),
+                default_score: 0,
+                scores: vec![
+                    PreferenceScore { row_id: ItemId(1), column_id: ItemId(3), score: 2 },
+                    PreferenceScore { row_id: ItemId(1), column_id: ItemId(5), score: 2 },
+                    PreferenceScore { row_id: ItemId(1), column_id: ItemId(7), score: 5 },
+                ],
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Each lecturer should teach in at most two different rooms
+        Constraint {
+            id: ConstraintId(18),
+            name: "Max 2 Rooms Per Lecturer".to_string(),
+            weight: 10,
+            rule: ConstraintRule::GlobalConsistency {
+                group_item_field: "Lecturer:id".to_string(),
+                resource_item_field: "Room:id".to_string(),
+                max_distinct: 2,
+                scope_conditions: None,
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Lecturers need time to walk between buildings
+        Constraint {
+            id: ConstraintId(19),
+            name: "Lecturer Travel Time".to_string(),
+            weight: 50,
+            rule: ConstraintRule::TravelTime {
+                resource_item_field: "Lecturer:id".to_string(),
+                location_item_field: "Room:building".to_string(),
+                scope_conditions: None,
+            },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
+        },
+        // Share teaching hours evenly among lecturers
+        Constraint {
+            id: ConstraintId(20),
+            name: "Balance Teaching Hours".to_string(),
+            weight: 1,
+            rule: ConstraintRule::GlobalBalance {
+                target_item_field: "Lecturer:id".to_string(),
+                sum_item_field: Some("Course:duration".to_string()),
+                measure: BalanceMeasure::AbsoluteDeviation,
+                scope_conditions: None,
             },
+            enabled: true,
+            penalty: PenaltyFunction::Linear,
         },
     ]
 }
@@ -1,6 +1,7 @@
 use axum::{Json, extract::{Path, State}, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
-use crate::{api_error::ApiError, app_state::AppState, domain::*};
+use std::sync::atomic::Ordering;
+use crate::{api_error::ApiError, app_state::AppState, application::validate_constraint, domain::*};
 
 #[derive(Serialize, Deserialize)]
 pub struct CreateConstraintRequest {
@@ -12,14 +13,39 @@ pub struct ConstraintsListResponse {
     pub constraints: Vec<Constraint>,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct CreateConstraintResponse {
+    pub message: String,
+    pub id: ConstraintId,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetConstraintEnabledRequest {
+    pub enabled: bool,
+}
+
 pub async fn create_constraint(
     State(state): State<AppState>,
     Json(request): Json<CreateConstraintRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    validate_constraint(&request.constraint, &problem_data).map_err(ApiError::InvalidConstraint)?;
+
     let mut constraints = state.constraints.write().await;
-    constraints.push(request.constraint);
-    
-    Ok((StatusCode::CREATED, Json(serde_json::json!({ "message": "Constraint created" }))))
+    if constraints.iter().any(|c| c.name == request.constraint.name) {
+        return Err(ApiError::DuplicateConstraintName);
+    }
+
+    let id = state.last_constraint_id
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| last.checked_add(1))
+        .map(|last| ConstraintId(last + 1))
+        .map_err(|_| ApiError::ConstraintIdsExhausted)?;
+    constraints.push(Constraint { id, ..request.constraint });
+
+    Ok((StatusCode::CREATED, Json(CreateConstraintResponse {
+        message: "Constraint created".to_string(),
+        id,
+    })))
 }
 
 pub async fn list_constraints(
@@ -31,31 +57,193 @@ pub async fn list_constraints(
 
 pub async fn update_constraint(
     State(state): State<AppState>,
-    Path(name): Path<String>,
+    Path(id): Path<u32>,
     Json(request): Json<CreateConstraintRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    validate_constraint(&request.constraint, &problem_data).map_err(ApiError::InvalidConstraint)?;
+
     let mut constraints = state.constraints.write().await;
+    let id = ConstraintId(id);
 
-    if let Some(existing) = constraints.iter_mut().find(|c| c.name == name) {
-        *existing = request.constraint;
+    if constraints.iter().any(|c| c.id != id && c.name == request.constraint.name) {
+        return Err(ApiError::DuplicateConstraintName);
+    }
+
+    if let Some(existing) = constraints.iter_mut().find(|c| c.id == id) {
+        *existing = Constraint { id, ..request.constraint };
         Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Constraint updated" }))))
     } else {
-        Err(ApiError::ItemNotFound)
+        Err(ApiError::ConstraintNotFound)
+    }
+}
+
+pub async fn set_constraint_enabled(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+    Json(request): Json<SetConstraintEnabledRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut constraints = state.constraints.write().await;
+
+    let constraint = constraints.iter_mut().find(|c| c.id == ConstraintId(id))
+        .ok_or(ApiError::ConstraintNotFound)?;
+    constraint.enabled = request.enabled;
+
+    let message = if request.enabled { "Constraint enabled" } else { "Constraint disabled" };
+    Ok((StatusCode::OK, Json(serde_json::json!({ "message": message }))))
+}
+
+/// A whole preference matrix: `scores[r][c]` is the score of
+/// `row_ids[r]` with `column_ids[c]`.
+#[derive(Serialize, Deserialize)]
+pub struct UploadPreferencesRequest {
+    pub row_ids: Vec<ItemId>,
+    pub column_ids: Vec<ItemId>,
+    pub scores: Vec<Vec<u32>>,
+    /// Keeps the constraint's current default when left out.
+    #[serde(default)]
+    pub default_score: Option<u32>,
+}
+
+/// Replaces every score of a `PreferenceMatrix` constraint at once. Cells
+/// equal to the default score are not stored.
+pub async fn upload_preferences(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+    Json(request): Json<UploadPreferencesRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    let mut constraints = state.constraints.write().await;
+
+    let constraint = constraints.iter_mut().find(|c| c.id == ConstraintId(id))
+        .ok_or(ApiError::ConstraintNotFound)?;
+    let mut updated = constraint.clone();
+    let ConstraintRule::PreferenceMatrix { default_score, scores, .. } = &mut updated.rule else {
+        return Err(ApiError::InvalidConstraint(vec!["rule: not a PreferenceMatrix".to_string()]));
+    };
+
+    let mut errors = Vec::new();
+    if request.scores.len() != request.row_ids.len() {
+        errors.push(format!("scores: expected {} rows, got {}", request.row_ids.len(), request.scores.len()));
+    }
+    for (index, row) in request.scores.iter().enumerate() {
+        if row.len() != request.column_ids.len() {
+            errors.push(format!("scores[{}]: expected {} columns, got {}", index, request.column_ids.len(), row.len()));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::InvalidConstraint(errors));
+    }
+
+    if let Some(default) = request.default_score {
+        *default_score = default;
     }
+    *scores = request.row_ids.iter().zip(&request.scores)
+        .flat_map(|(row_id, row)| request.column_ids.iter().zip(row).map(move |(column_id, score)| PreferenceScore {
+            row_id: *row_id,
+            column_id: *column_id,
+            score: *score,
+        }))
+        .filter(|s| s.score != *default_score)
+        .collect();
+
+    validate_constraint(&updated, &problem_data).map_err(ApiError::InvalidConstraint)?;
+    *constraint = updated;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Preferences updated" }))))
 }
 
 pub async fn delete_constraint(
     State(state): State<AppState>,
-    Path(name): Path<String>,
+    Path(id): Path<u32>,
 ) -> Result<impl IntoResponse, ApiError> {
     let mut constraints = state.constraints.write().await;
 
     let before = constraints.len();
-    constraints.retain(|c| c.name != name);
+    constraints.retain(|c| c.id != ConstraintId(id));
 
     if constraints.len() < before {
         Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Constraint deleted" }))))
     } else {
-        Err(ApiError::ItemNotFound)
+        Err(ApiError::ConstraintNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    fn rooms_differ(name: &str) -> CreateConstraintRequest {
+        let mut constraint = constraint(0, json!({ "GlobalAllDifferent": {
+            "unique_item_field": "Room:id", "group_item_field": "TimeSlot:id",
+        } }));
+        constraint.name = name.to_string();
+        CreateConstraintRequest { constraint }
+    }
+
+    async fn create(state: &AppState, name: &str) -> ConstraintId {
+        let response = create_constraint(State(state.clone()), Json(rooms_differ(name)))
+            .await
+            .ok()
+            .expect("constraint created")
+            .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<CreateConstraintResponse>(&body).unwrap().id
+    }
+
+    #[tokio::test]
+    async fn ids_of_deleted_constraints_are_not_reused() {
+        let state = AppState::new(timetable(), vec![constraint(3, json!({ "CurriculumConflict": {} }))], None);
+
+        let first = create(&state, "First").await;
+        assert_eq!(first, ConstraintId(4));
+        assert!(delete_constraint(State(state.clone()), Path(first.0)).await.is_ok());
+        assert_eq!(create(&state, "Second").await, ConstraintId(5));
+    }
+
+    #[tokio::test]
+    async fn names_stay_unique_and_disabled_constraints_are_kept() {
+        let state = AppState::new(timetable(), Vec::new(), None);
+        let first = create(&state, "Rooms").await;
+        let second = create(&state, "Rooms elsewhere").await;
+
+        let duplicate = create_constraint(State(state.clone()), Json(rooms_differ("Rooms"))).await;
+        assert!(matches!(duplicate, Err(ApiError::DuplicateConstraintName)));
+        let renamed = update_constraint(State(state.clone()), Path(second.0), Json(rooms_differ("Rooms"))).await;
+        assert!(matches!(renamed, Err(ApiError::DuplicateConstraintName)));
+        let kept = update_constraint(State(state.clone()), Path(first.0), Json(rooms_differ("Rooms"))).await;
+        assert!(kept.is_ok());
+
+        let disabled = set_constraint_enabled(State(state.clone()), Path(first.0), Json(SetConstraintEnabledRequest { enabled: false })).await;
+        assert!(disabled.is_ok());
+        let constraints = state.constraints.read().await;
+        assert_eq!(constraints.len(), 2);
+        assert!(!constraints[0].enabled && constraints[1].enabled);
+        assert_eq!(constraints[0].id, first);
+    }
+
+    #[tokio::test]
+    async fn uploaded_matrices_keep_only_cells_off_the_default() {
+        let state = AppState::new(timetable(), vec![preferences(1, 0, &[(1, 1, 5)])], None);
+        let upload = |scores: Vec<Vec<u32>>| upload_preferences(State(state.clone()), Path(1), Json(UploadPreferencesRequest {
+            row_ids: vec![ItemId(1), ItemId(2)],
+            column_ids: vec![ItemId(1), ItemId(2), ItemId(3)],
+            scores,
+            default_score: Some(1),
+        }));
+
+        let uneven = upload(vec![vec![1, 2, 1], vec![1, 1]]).await;
+        assert!(matches!(uneven, Err(ApiError::InvalidConstraint(errors)) if errors == ["scores[1]: expected 3 columns, got 2"]));
+        assert!(upload(vec![vec![1, 2, 1], vec![3, 1, 1]]).await.is_ok());
+
+        let constraints = state.constraints.read().await;
+        let ConstraintRule::PreferenceMatrix { default_score, scores, .. } = &constraints[0].rule else {
+            panic!("not a preference matrix");
+        };
+        let cells: Vec<(u32, u32, u32)> = scores.iter().map(|s| (s.row_id.0, s.column_id.0, s.score)).collect();
+        assert_eq!(*default_score, 1);
+        assert_eq!(cells, [(1, 2, 2), (2, 1, 3)]);
     }
 }
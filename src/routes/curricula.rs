@@ -0,0 +1,90 @@
+use axum::{Json, extract::{Path, Query, State}, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use crate::{api_error::ApiError, app_state::AppState, application::{curriculum_dependents, remove_dependents, validate_curriculum, DeleteMode}, domain::*};
+use super::items::{DeleteParams, DeleteResponse};
+
+#[derive(Serialize, Deserialize)]
+pub struct CurriculaListResponse {
+    pub curricula: Vec<Curriculum>,
+}
+
+pub async fn create_curriculum(
+    State(state): State<AppState>,
+    Json(curriculum): Json<Curriculum>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+    validate_curriculum(&curriculum, &problem_data).map_err(ApiError::InvalidCurriculum)?;
+
+    if problem_data.curricula.iter().any(|c| c.name == curriculum.name) {
+        return Err(ApiError::DuplicateCurriculumName);
+    }
+
+    problem_data.curricula.push(curriculum);
+
+    Ok((StatusCode::CREATED, Json(serde_json::json!({ "message": "Curriculum created" }))))
+}
+
+pub async fn list_curricula(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    Ok(Json(CurriculaListResponse { curricula: problem_data.curricula.clone() }))
+}
+
+/// Replaces a curriculum; a new name is carried over to the constraints that
+/// check it.
+pub async fn update_curriculum(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(curriculum): Json<Curriculum>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+    let mut constraints = state.constraints.write().await;
+    validate_curriculum(&curriculum, &problem_data).map_err(ApiError::InvalidCurriculum)?;
+
+    if curriculum.name != name && problem_data.curricula.iter().any(|c| c.name == curriculum.name) {
+        return Err(ApiError::DuplicateCurriculumName);
+    }
+
+    let existing = problem_data.curricula.iter_mut()
+        .find(|c| c.name == name)
+        .ok_or(ApiError::CurriculumNotFound)?;
+
+    for constraint in constraints.iter_mut() {
+        if let ConstraintRule::CurriculumConflict { curricula } = &mut constraint.rule {
+            for referenced in curricula.iter_mut().filter(|n| **n == name) {
+                *referenced = curriculum.name.clone();
+            }
+        }
+    }
+    *existing = curriculum;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Curriculum updated" }))))
+}
+
+pub async fn delete_curriculum(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(params): Query<DeleteParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+    let mut constraints = state.constraints.write().await;
+    let mut schedule = state.schedule.write().await;
+
+    if !problem_data.curricula.iter().any(|c| c.name == name) {
+        return Err(ApiError::CurriculumNotFound);
+    }
+
+    let dependents = curriculum_dependents(&name, &constraints);
+    if !dependents.is_empty() && params.on_delete == DeleteMode::Restrict {
+        return Err(ApiError::HasDependents(dependents.iter().map(|d| d.to_string()).collect()));
+    }
+
+    remove_dependents(&dependents, &mut problem_data, &mut constraints, schedule.as_mut());
+    problem_data.curricula.retain(|c| c.name != name);
+
+    Ok((StatusCode::OK, Json(DeleteResponse {
+        message: "Curriculum deleted".to_string(),
+        removed: dependents.iter().map(|d| d.to_string()).collect(),
+    })))
+}
@@ -0,0 +1,47 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use crate::{api_error::ApiError, app_state::AppState, application::validate_dimensions, domain::*};
+
+#[derive(Serialize, Deserialize)]
+pub struct DimensionsResponse {
+    /// The dimensions rules and views use, defaults included.
+    pub time: TimeDimension,
+    pub space: SpaceDimension,
+    /// Whether each dimension is designated rather than defaulted.
+    pub time_designated: bool,
+    pub space_designated: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetDimensionsRequest {
+    /// `None` falls back to the default.
+    pub time_dimension: Option<TimeDimension>,
+    pub space_dimension: Option<SpaceDimension>,
+}
+
+pub async fn get_dimensions(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    Ok(Json(DimensionsResponse {
+        time: problem_data.time().clone(),
+        space: problem_data.space().clone(),
+        time_designated: problem_data.time_dimension.is_some(),
+        space_designated: problem_data.space_dimension.is_some(),
+    }))
+}
+
+/// Replaces both designations.
+pub async fn set_dimensions(
+    State(state): State<AppState>,
+    Json(request): Json<SetDimensionsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+    validate_dimensions(request.time_dimension.as_ref(), request.space_dimension.as_ref(), &problem_data)
+        .map_err(ApiError::InvalidDimensions)?;
+
+    problem_data.time_dimension = request.time_dimension;
+    problem_data.space_dimension = request.space_dimension;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Dimensions updated" }))))
+}
@@ -0,0 +1,20 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use crate::{api_error::ApiError, app_state::AppState, application::{find_broken_references, BrokenReference}};
+
+#[derive(Serialize, Deserialize)]
+pub struct IntegrityResponse {
+    pub broken_references: Vec<BrokenReference>,
+}
+
+pub async fn check_integrity(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    let constraints = state.constraints.read().await;
+    let schedule = state.schedule.read().await;
+
+    let broken_references = find_broken_references(&problem_data, &constraints, schedule.as_ref());
+
+    Ok(Json(IntegrityResponse { broken_references }))
+}
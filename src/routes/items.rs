@@ -1,6 +1,6 @@
-use axum::{Json, extract::{Path, State}, http::StatusCode, response::IntoResponse};
+use axum::{Json, extract::{Path, Query, State}, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
-use crate::{api_error::ApiError, app_state::AppState, domain::*};
+use crate::{api_error::ApiError, app_state::AppState, application::{item_dependents, migrate_item, remove_dependents, DeleteMode, MigrationReport, SchemaChange}, domain::*};
 
 #[derive(Serialize, Deserialize)]
 pub struct CreateItemRequest {
@@ -32,6 +32,7 @@ pub async fn create_item(
         item_set_type: request.item_set_type,
         members: vec![],
         schema: request.schema,
+        last_member_id: 0,
     };
     
     problem_data.item_categories.insert(request.name, item);
@@ -77,15 +78,73 @@ pub async fn update_item(
     Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Item updated" }))))
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct MigrateItemRequest {
+    pub changes: Vec<SchemaChange>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MigrateItemResponse {
+    pub message: String,
+    pub report: MigrationReport,
+}
+
+pub async fn migrate_item_schema(
+    State(state): State<AppState>,
+    Path(item_name): Path<String>,
+    Json(request): Json<MigrateItemRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+    let mut constraints = state.constraints.write().await;
+
+    if !problem_data.item_categories.contains_key(&item_name) {
+        return Err(ApiError::ItemNotFound);
+    }
+
+    let report = migrate_item(&mut problem_data, &mut constraints, &item_name, &request.changes)
+        .map_err(ApiError::InvalidSchemaChange)?;
+
+    Ok((StatusCode::OK, Json(MigrateItemResponse {
+        message: "Item schema migrated".to_string(),
+        report,
+    })))
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeleteParams {
+    #[serde(default)]
+    pub on_delete: DeleteMode,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DeleteResponse {
+    pub message: String,
+    pub removed: Vec<String>,
+}
+
 pub async fn delete_item(
     State(state): State<AppState>,
     Path(item_name): Path<String>,
+    Query(params): Query<DeleteParams>,
 ) -> Result<impl IntoResponse, ApiError> {
     let mut problem_data = state.problem_data.write().await;
+    let mut constraints = state.constraints.write().await;
+    let mut schedule = state.schedule.write().await;
+
+    if !problem_data.item_categories.contains_key(&item_name) {
+        return Err(ApiError::ItemNotFound);
+    }
 
-    if problem_data.item_categories.remove(&item_name).is_some() {
-        Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Item deleted" }))))
-    } else {
-        Err(ApiError::ItemNotFound)
+    let dependents = item_dependents(&problem_data, &item_name, &constraints, schedule.as_ref());
+    if !dependents.is_empty() && params.on_delete == DeleteMode::Restrict {
+        return Err(ApiError::HasDependents(dependents.iter().map(|d| d.to_string()).collect()));
     }
+
+    remove_dependents(&dependents, &mut problem_data, &mut constraints, schedule.as_mut());
+    problem_data.item_categories.remove(&item_name);
+
+    Ok((StatusCode::OK, Json(DeleteResponse {
+        message: "Item deleted".to_string(),
+        removed: dependents.iter().map(|d| d.to_string()).collect(),
+    })))
 }
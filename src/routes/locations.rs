@@ -0,0 +1,33 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use crate::{api_error::ApiError, app_state::AppState, application::validate_distances, domain::*};
+
+#[derive(Serialize, Deserialize)]
+pub struct DistancesResponse {
+    pub distances: Vec<LocationDistance>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetDistancesRequest {
+    pub distances: Vec<LocationDistance>,
+}
+
+pub async fn list_distances(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    Ok(Json(DistancesResponse { distances: problem_data.location_distances.clone() }))
+}
+
+/// Replaces the whole distance matrix.
+pub async fn set_distances(
+    State(state): State<AppState>,
+    Json(request): Json<SetDistancesRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_distances(&request.distances).map_err(ApiError::InvalidDistances)?;
+
+    let mut problem_data = state.problem_data.write().await;
+    problem_data.location_distances = request.distances;
+
+    Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Distances updated" }))))
+}
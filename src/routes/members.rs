@@ -1,17 +1,31 @@
-use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
+use axum::{extract::{Path, Query, State}, http::StatusCode, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
-use crate::{api_error::ApiError, app_state::AppState, domain::*};
+use crate::{api_error::ApiError, app_state::AppState, application::{member_dependents, remove_dependents, validate_availability, DeleteMode}, domain::*};
+use super::items::{DeleteParams, DeleteResponse};
 
 #[derive(Serialize, Deserialize)]
 pub struct AddMemberRequest {
-    pub id: ItemId,
+    /// Left out to let the server allocate one; given explicitly for imports.
+    #[serde(default)]
+    pub id: Option<ItemId>,
     pub fields: std::collections::HashMap<String, Value>,
+    /// Left out on update to keep the member's current windows.
+    #[serde(default)]
+    pub availability: Option<Vec<AvailabilityWindow>>,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct MemberResponse {
     pub id: ItemId,
     pub fields: std::collections::HashMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub availability: Vec<AvailabilityWindow>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddMemberResponse {
+    pub message: String,
+    pub id: ItemId,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -28,15 +42,30 @@ pub async fn add_member(
     
     let item = problem_data.item_categories.get_mut(&item_name)
         .ok_or(ApiError::ItemNotFound)?;
+    let availability = request.availability.unwrap_or_default();
+    validate_availability(item, &availability).map_err(ApiError::InvalidMember)?;
     
+    let id = match request.id {
+        Some(id) if item.member(id).is_some() => return Err(ApiError::DuplicateMemberId),
+        Some(id) => {
+            item.last_member_id = item.last_member_id.max(id.0);
+            id
+        }
+        None => item.allocate_member_id().ok_or(ApiError::MemberIdsExhausted)?,
+    };
+
     let member = Member {
-        id: request.id,
+        id,
         fields: request.fields,
+        availability,
     };
     
     item.members.push(member);
     
-    Ok((StatusCode::CREATED, Json(serde_json::json!({ "message": "Member added" }))))
+    Ok((StatusCode::CREATED, Json(AddMemberResponse {
+        message: "Member added".to_string(),
+        id,
+    })))
 }
 
 pub async fn list_members(
@@ -52,6 +81,7 @@ pub async fn list_members(
         .map(|m| MemberResponse {
             id: m.id,
             fields: m.fields.clone(),
+            availability: m.availability.clone(),
         })
         .collect();
     
@@ -67,9 +97,15 @@ pub async fn update_member(
 
     let item = problem_data.item_categories.get_mut(&item_name)
         .ok_or(ApiError::ItemNotFound)?;
+    if let Some(availability) = &request.availability {
+        validate_availability(item, availability).map_err(ApiError::InvalidMember)?;
+    }
 
     if let Some(member) = item.members.iter_mut().find(|m| m.id.0 == member_id) {
         member.fields = request.fields;
+        if let Some(availability) = request.availability {
+            member.availability = availability;
+        }
         Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Member updated" }))))
     } else {
         Err(ApiError::MemberNotFound)
@@ -79,18 +115,61 @@ pub async fn update_member(
 pub async fn delete_member(
     State(state): State<AppState>,
     Path((item_name, member_id)): Path<(String, u32)>,
+    Query(params): Query<DeleteParams>,
 ) -> Result<impl IntoResponse, ApiError> {
     let mut problem_data = state.problem_data.write().await;
+    let mut constraints = state.constraints.write().await;
+    let mut schedule = state.schedule.write().await;
 
-    let item = problem_data.item_categories.get_mut(&item_name)
+    let item = problem_data.item_categories.get(&item_name)
         .ok_or(ApiError::ItemNotFound)?;
 
-    let before = item.members.len();
-    item.members.retain(|m| m.id.0 != member_id);
+    let member_id = ItemId(member_id);
+    if item.member(member_id).is_none() {
+        return Err(ApiError::MemberNotFound);
+    }
+
+    let dependents = member_dependents(&problem_data, &item_name, member_id, &constraints, schedule.as_ref());
+    if !dependents.is_empty() && params.on_delete == DeleteMode::Restrict {
+        return Err(ApiError::HasDependents(dependents.iter().map(|d| d.to_string()).collect()));
+    }
 
-    if item.members.len() < before {
-        Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Member deleted" }))))
-    } else {
-        Err(ApiError::MemberNotFound)
+    remove_dependents(&dependents, &mut problem_data, &mut constraints, schedule.as_mut());
+    if let Some(item) = problem_data.item_categories.get_mut(&item_name) {
+        item.members.retain(|m| m.id != member_id);
+    }
+
+    Ok((StatusCode::OK, Json(DeleteResponse {
+        message: "Member deleted".to_string(),
+        removed: dependents.iter().map(|d| d.to_string()).collect(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn deleting_a_referenced_member_is_restricted_unless_cascading() {
+        let constraints = vec![constraint(1, json!({ "MultiAssignmentCheck": {
+            "conditions": [condition("Room", "id", "In", &["1", "2"])],
+            "logical_op": "Or", "mode": "Forbid",
+        } }))];
+        let state = AppState::new(timetable(), constraints, Some(Schedule::new(vec![lesson(1, 1, 1, 1)])));
+        let delete = |on_delete| delete_member(
+            State(state.clone()),
+            Path(("Room".to_string(), 1)),
+            Query(DeleteParams { on_delete }),
+        );
+
+        assert!(matches!(delete(DeleteMode::Restrict).await, Err(ApiError::HasDependents(dependents)) if dependents.len() == 2));
+        assert!(state.problem_data.read().await.item_categories["Room"].member(ItemId(1)).is_some());
+
+        assert!(delete(DeleteMode::Cascade).await.is_ok());
+        assert!(state.problem_data.read().await.item_categories["Room"].member(ItemId(1)).is_none());
+        assert!(state.constraints.read().await.is_empty());
+        assert!(state.schedule.read().await.as_ref().unwrap().assignments.is_empty());
     }
 }
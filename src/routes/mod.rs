@@ -2,8 +2,20 @@ mod items;
 mod members;
 mod constraints;
 mod solve;
+mod integrity;
+mod curricula;
+mod pairings;
+mod locations;
+mod dimensions;
+mod timeslots;
 
 pub use items::*;
 pub use members::*;
 pub use constraints::*;
 pub use solve::*;
+pub use integrity::*;
+pub use curricula::*;
+pub use pairings::*;
+pub use locations::*;
+pub use dimensions::*;
+pub use timeslots::*;
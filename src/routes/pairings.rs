@@ -0,0 +1,61 @@
+use axum::{Json, extract::{Path, State}, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use crate::{api_error::ApiError, app_state::AppState, application::validate_pairing, domain::*};
+
+#[derive(Serialize, Deserialize)]
+pub struct PairingsListResponse {
+    pub pairings: Vec<ResourcePairing>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetPairingRequest {
+    pub rule: PairingRule,
+}
+
+pub async fn list_pairings(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let problem_data = state.problem_data.read().await;
+    Ok(Json(PairingsListResponse { pairings: problem_data.pairings.clone() }))
+}
+
+/// Creates or replaces the pairing between a task item and a resource item.
+pub async fn set_pairing(
+    State(state): State<AppState>,
+    Path((task_item, resource_item)): Path<(String, String)>,
+    Json(request): Json<SetPairingRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+
+    let pairing = ResourcePairing { task_item, resource_item, rule: request.rule };
+    validate_pairing(&pairing, &problem_data).map_err(ApiError::InvalidPairing)?;
+
+    let existing = problem_data.pairings.iter_mut()
+        .find(|p| p.task_item == pairing.task_item && p.resource_item == pairing.resource_item);
+    match existing {
+        Some(existing) => {
+            *existing = pairing;
+            Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Pairing updated" }))))
+        }
+        None => {
+            problem_data.pairings.push(pairing);
+            Ok((StatusCode::CREATED, Json(serde_json::json!({ "message": "Pairing created" }))))
+        }
+    }
+}
+
+pub async fn delete_pairing(
+    State(state): State<AppState>,
+    Path((task_item, resource_item)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+
+    let before = problem_data.pairings.len();
+    problem_data.pairings.retain(|p| p.task_item != task_item || p.resource_item != resource_item);
+
+    if problem_data.pairings.len() < before {
+        Ok((StatusCode::OK, Json(serde_json::json!({ "message": "Pairing deleted" }))))
+    } else {
+        Err(ApiError::PairingNotFound)
+    }
+}
@@ -1,6 +1,6 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use serde::{Deserialize, Serialize};
-use crate::{api_error::ApiError, app_state::AppState, application::SimulatedAnnealing, domain::*};
+use crate::{api_error::ApiError, app_state::AppState, application::{CompiledProblem, ConstraintReport, SimulatedAnnealing}, domain::*};
 use rand::seq::SliceRandom;
 
 #[derive(Serialize, Deserialize)]
@@ -14,6 +14,8 @@ pub struct SolveRequest {
 pub struct SolveResponse {
     pub schedule: Schedule,
     pub final_cost: u32,
+    /// Violations and penalty of each enabled constraint in `schedule`.
+    pub constraint_reports: Vec<ConstraintReport>,
 }
 
 pub async fn solve(
@@ -31,12 +33,16 @@ pub async fn solve(
         request.max_iterations,
     );
     
-    let best_schedule = solver.solve(&problem_data, &constraints, initial_schedule);
-    let final_cost = crate::application::evaluate_schedule(&best_schedule, &problem_data, &constraints);
+    let compiled = CompiledProblem::new(&problem_data, &constraints);
+    let best_schedule = solver.solve(&compiled, initial_schedule);
+    let final_cost = compiled.evaluate(&best_schedule);
+    let constraint_reports = compiled.report(&best_schedule);
+    *state.schedule.write().await = Some(best_schedule.clone());
     
     Ok((StatusCode::OK, Json(SolveResponse {
         schedule: best_schedule,
         final_cost,
+        constraint_reports,
     })))
 }
 
@@ -54,9 +60,11 @@ fn create_random_schedule(problem_data: &ProblemData) -> Schedule {
         for course in &course_item.members {
             let mut resources = std::collections::HashMap::new();
             
+            // A resource no member is paired with stays unassigned
             for e_item in &e_set_items {
-                if let Some(member) = e_item.members.choose(&mut rng) {
-                    resources.insert(e_item.name.clone(), member.id);
+                let candidates = problem_data.allowed_resources(&course_item.name, course.id, &e_item.name);
+                if let Some(member) = candidates.choose(&mut rng) {
+                    resources.insert(e_item.name.clone(), *member);
                 }
             }
 
@@ -0,0 +1,71 @@
+use axum::{Json, extract::{Query, State}, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use crate::{api_error::ApiError, app_state::AppState, application::{member_dependents, plan_slots, remap_member_ids, remove_dependents, DeleteMode, SlotTemplate}};
+use super::items::DeleteParams;
+
+#[derive(Serialize, Deserialize)]
+pub struct GenerateSlotsRequest {
+    #[serde(flatten)]
+    pub template: SlotTemplate,
+    /// Replaces the existing slots instead of adding the missing ones.
+    #[serde(default)]
+    pub replace: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GenerateSlotsResponse {
+    pub message: String,
+    pub item_name: String,
+    pub slots: usize,
+    pub created: usize,
+    /// Existing slots that were kept, under their new id where it changed.
+    pub kept: usize,
+    pub deleted: usize,
+    /// Dependents removed along with the deleted slots.
+    pub removed: Vec<String>,
+}
+
+/// Fills the time dimension's item from a weekly template, creating the
+/// item if needed. Deleting slots that are still referenced follows
+/// `on_delete` like deleting members does.
+pub async fn generate_timeslots(
+    State(state): State<AppState>,
+    Query(params): Query<DeleteParams>,
+    Json(request): Json<GenerateSlotsRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let mut problem_data = state.problem_data.write().await;
+    let mut constraints = state.constraints.write().await;
+    let mut schedule = state.schedule.write().await;
+
+    let plan = plan_slots(&request.template, request.replace, &problem_data)
+        .map_err(ApiError::InvalidSlotTemplate)?;
+    let item_name = plan.item.name.clone();
+
+    let mut dependents = Vec::new();
+    for member_id in &plan.removed {
+        for dependent in member_dependents(&problem_data, &item_name, *member_id, &constraints, schedule.as_ref()) {
+            if !dependents.contains(&dependent) {
+                dependents.push(dependent);
+            }
+        }
+    }
+    if !dependents.is_empty() && params.on_delete == DeleteMode::Restrict {
+        return Err(ApiError::HasDependents(dependents.iter().map(|d| d.to_string()).collect()));
+    }
+
+    remove_dependents(&dependents, &mut problem_data, &mut constraints, schedule.as_mut());
+    remap_member_ids(&item_name, &plan.renumbered, &mut problem_data, &mut constraints, schedule.as_mut());
+
+    let response = GenerateSlotsResponse {
+        message: "Time slots generated".to_string(),
+        item_name: item_name.clone(),
+        slots: plan.item.members.len(),
+        created: plan.created,
+        kept: plan.item.members.len() - plan.created,
+        deleted: plan.removed.len(),
+        removed: dependents.iter().map(|d| d.to_string()).collect(),
+    };
+    problem_data.item_categories.insert(item_name, plan.item);
+
+    Ok((StatusCode::OK, Json(response)))
+}
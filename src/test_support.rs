@@ -0,0 +1,176 @@
+//! Small problems for unit tests.
+
+use crate::domain::*;
+use serde_json::json;
+use std::collections::HashMap;
+
+pub fn text(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+pub fn clock(s: &str) -> Value {
+    Value::Date(s.to_string())
+}
+
+pub fn member(id: u32, fields: &[(&str, Value)]) -> Member {
+    Member {
+        id: ItemId(id),
+        fields: fields.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+        availability: Vec::new(),
+    }
+}
+
+pub fn item(name: &str, item_set_type: SetType, fields: &[(&str, FieldType)], members: Vec<Member>) -> Item {
+    let definitions = fields.iter()
+        .map(|(field, field_type)| (field.to_string(), FieldSchema {
+            field_name: field.to_string(),
+            field_type: field_type.clone(),
+            is_required: true,
+        }))
+        .collect();
+    Item { name: name.to_string(), item_set_type, members, schema: Schema { definitions }, last_member_id: 0 }
+}
+
+pub fn problem(items: Vec<Item>) -> ProblemData {
+    ProblemData {
+        item_categories: items.into_iter().map(|item| (item.name.clone(), item)).collect(),
+        curricula: Vec::new(),
+        pairings: Vec::new(),
+        location_distances: Vec::new(),
+        time_dimension: None,
+        space_dimension: None,
+    }
+}
+
+/// Four courses, three rooms, two lecturers and four one-hour slots on
+/// Monday and Tuesday.
+///
+/// Courses 1 and 2 belong to module "A", 3 and 4 to "B"; odd courses are
+/// lectures. Room 1 seats 20 in building "North", rooms 2 and 3 seat 60 in
+/// "South".
+pub fn timetable() -> ProblemData {
+    let course = |id: u32, module: &str, kind: &str, students: i32| member(id, &[
+        ("name", text(&format!("Course {}", id))),
+        ("module", text(module)),
+        ("kind", text(kind)),
+        ("students", Value::Number(students)),
+        ("duration", Value::Number(90)),
+    ]);
+    let room = |id: u32, capacity: i32, building: &str| member(id, &[
+        ("name", text(&format!("Room {}", id))),
+        ("capacity", Value::Number(capacity)),
+        ("building", text(building)),
+    ]);
+    let slot = |id: u32, day: &str, start: &str, end: &str| member(id, &[
+        ("day", text(day)),
+        ("start", clock(start)),
+        ("end", clock(end)),
+    ]);
+
+    problem(vec![
+        item("Course", SetType::B_Set, &[
+            ("name", FieldType::Text),
+            ("module", FieldType::Text),
+            ("kind", FieldType::Text),
+            ("students", FieldType::Integer),
+            ("duration", FieldType::Integer),
+        ], vec![
+            course(1, "A", "lecture", 40),
+            course(2, "A", "exercise", 15),
+            course(3, "B", "lecture", 50),
+            course(4, "B", "exercise", 20),
+        ]),
+        item("Room", SetType::E_Set, &[
+            ("name", FieldType::Text),
+            ("capacity", FieldType::Integer),
+            ("building", FieldType::Text),
+        ], vec![room(1, 20, "North"), room(2, 60, "South"), room(3, 60, "South")]),
+        item("Lecturer", SetType::E_Set, &[("name", FieldType::Text)], vec![
+            member(1, &[("name", text("Ada"))]),
+            member(2, &[("name", text("Grace"))]),
+        ]),
+        item("TimeSlot", SetType::E_Set, &[
+            ("day", FieldType::Text),
+            ("start", FieldType::DateTime),
+            ("end", FieldType::DateTime),
+        ], vec![
+            slot(1, "Monday", "08:00", "09:00"),
+            slot(2, "Monday", "09:00", "10:00"),
+            slot(3, "Monday", "11:00", "12:00"),
+            slot(4, "Tuesday", "08:00", "09:00"),
+        ]),
+    ])
+}
+
+/// Course `course` in `room`, taught by `lecturer` in `slot`.
+pub fn lesson(course: u32, room: u32, lecturer: u32, slot: u32) -> Assignment {
+    Assignment {
+        task_id: ItemId(course),
+        task_item_name: "Course".to_string(),
+        resources: HashMap::from([
+            ("Room".to_string(), ItemId(room)),
+            ("Lecturer".to_string(), ItemId(lecturer)),
+            ("TimeSlot".to_string(), ItemId(slot)),
+        ]),
+    }
+}
+
+/// An enabled constraint with weight 1 and a linear penalty; `rule` is the
+/// rule's JSON, e.g. `json!({ "CurriculumConflict": {} })`.
+pub fn constraint(id: u32, rule: serde_json::Value) -> Constraint {
+    serde_json::from_value(json!({
+        "id": id, "name": format!("Constraint {}", id), "weight": 1, "rule": rule,
+    }))
+    .expect("test constraint")
+}
+
+/// A condition comparing `item:field` with fixed target values.
+pub fn condition(item: &str, field: &str, operator: &str, targets: &[&str]) -> serde_json::Value {
+    json!({ "item_name": item, "field_key": field, "operator": operator, "target_values": targets })
+}
+
+/// A curriculum of courses with `students` students.
+pub fn curriculum(name: &str, tasks: &[u32], students: Option<u32>) -> Curriculum {
+    Curriculum {
+        name: name.to_string(),
+        task_item: "Course".to_string(),
+        tasks: tasks.iter().map(|&id| ItemId(id)).collect(),
+        students,
+    }
+}
+
+/// An availability window on `weekday` from `start` to `end`, each optional.
+pub fn window(kind: AvailabilityKind, weekday: Option<&str>, start: Option<&str>, end: Option<&str>) -> AvailabilityWindow {
+    AvailabilityWindow {
+        kind,
+        weekday: weekday.map(str::to_string),
+        date: None,
+        start: start.map(str::to_string),
+        end: end.map(str::to_string),
+    }
+}
+
+/// A lecturer-by-slot preference matrix; `scores` are `(lecturer, slot, score)`.
+pub fn preferences(id: u32, default_score: u32, scores: &[(u32, u32, u32)]) -> Constraint {
+    let scores: Vec<serde_json::Value> = scores.iter()
+        .map(|(row_id, column_id, score)| json!({ "row_id": row_id, "column_id": column_id, "score": score }))
+        .collect();
+    constraint(id, json!({ "PreferenceMatrix": {
+        "row_item": "Lecturer", "column_item": "TimeSlot", "default_score": default_score, "scores": scores,
+    } }))
+}
+
+/// A pairing table of `(course, lecturers)` rows.
+pub fn lecturer_table(rows: &[(u32, &[u32])]) -> ResourcePairing {
+    let allowed = rows.iter()
+        .map(|(task_id, resource_ids)| AllowedResources {
+            task_id: ItemId(*task_id),
+            resource_ids: resource_ids.iter().map(|&id| ItemId(id)).collect(),
+        })
+        .collect();
+    ResourcePairing {
+        task_item: "Course".to_string(),
+        resource_item: "Lecturer".to_string(),
+        rule: PairingRule::Table { allowed },
+    }
+}
@@ -0,0 +1,41 @@
+//! Pins the cost and per-constraint reports of generated schedules, so that
+//! changes meant to speed the evaluator up can be checked to leave its results
+//! alone.
+//!
+//! The snapshot was written by the evaluator that searched the problem data
+//! directly, before it was compiled. To rewrite it after a deliberate change
+//! in behavior, run `UPDATE_SNAPSHOTS=1 cargo test --test evaluate_snapshot`.
+
+#[path = "../benches/support/mod.rs"]
+mod support;
+
+use scheduling_optimizer::application::{evaluate_schedule, report_schedule};
+use std::fmt::Write;
+use support::{build_problem, random_schedules};
+
+const SNAPSHOT: &str = "tests/snapshots/evaluate_reports.txt";
+
+#[test]
+fn reports_match_the_snapshot() {
+    let mut actual = String::new();
+    for courses in [40, 400] {
+        let (problem_data, constraints) = build_problem(courses);
+        for schedule in random_schedules(&problem_data, 16) {
+            let cost = evaluate_schedule(&schedule, &problem_data, &constraints);
+            write!(actual, "{} {}", courses, cost).unwrap();
+            for report in report_schedule(&schedule, &problem_data, &constraints) {
+                write!(actual, " {}/{}", report.violations, report.penalty).unwrap();
+            }
+            actual.push('\n');
+        }
+    }
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(SNAPSHOT, &actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(SNAPSHOT).unwrap();
+    for (line, (actual, expected)) in actual.lines().zip(expected.lines()).enumerate() {
+        assert_eq!(actual, expected, "line {} of {}", line + 1, SNAPSHOT);
+    }
+    assert_eq!(actual.lines().count(), expected.lines().count(), "lines in {}", SNAPSHOT);
+}